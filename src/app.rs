@@ -6,20 +6,29 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     Frame,
 };
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::detect;
+use crate::env;
 use crate::gh;
+use crate::health::Health;
 use crate::git_status::GitStatus;
 use crate::git_worker::GitWorker;
+use crate::launch;
+use crate::logs;
+use crate::pipeline::{self, Action, Kind, Pipeline, Step, StepState};
 use crate::ports::{self, PortInfo};
-use crate::process::ProcessManager;
+use crate::process::{self, PendingStop, ProcessManager};
 use crate::scanner;
-use crate::store::{ProjectEntry, ProjectStore};
+use crate::store::{ProjectEntry, ProjectStore, ServiceEntry};
 use crate::theme;
+use crate::ui::ansi;
 use crate::ui::input::InputDialog;
+use crate::ui::log_view::LogView;
 use crate::ui::selector::RepoSelector;
-use crate::updater::{self, UpdateChecker, UpdateDownloader, UpdateInfo};
+use crate::updater::{UpdateChecker, UpdateDownloader, UpdateInfo};
+use crate::workspace::WorkspacePackage;
 
 #[derive(Default, PartialEq)]
 enum InputMode {
@@ -31,14 +40,55 @@ enum InputMode {
     ImportPath,
     SetInstallDir,
     ClonePath,
+    SelectLog,
+    SelectPackages,
+    ViewLogs,
+    SendInput,
+    PortConflict,
     ConfirmQuit,
 }
 
+/// A project whose pinned port is held by something else, waiting for the
+/// user to pick a way out.
+struct PortConflict {
+    project: String,
+    /// Set when only this service of the project is being run
+    service: Option<String>,
+    holder: PortInfo,
+    /// Process key of ours holding the port, if it is one of ours
+    owner: Option<String>,
+    /// Free port to run on instead
+    fallback: Option<u16>,
+    /// Why killing the holder failed
+    error: Option<String>,
+    /// The holder being stopped; the run starts once it is gone
+    freeing: Option<PendingStop>,
+}
+
+/// Keyboard input being forwarded from the logs pane to a running process.
+struct StdinInput {
+    /// Process key the input goes to
+    key: String,
+    /// The line being typed in line mode; None sends each key as it is pressed
+    line: Option<String>,
+    /// Why the last input couldn't be sent
+    error: Option<String>,
+}
+
+/// A row in the project list: a project, or one of its services nested under it.
+#[derive(Clone, Copy)]
+struct ListRow {
+    project: usize,
+    service: Option<usize>,
+}
+
 pub struct App {
     pub store: ProjectStore,
     pub list_state: ListState,
     pub selected_detection: Option<detect::DetectedProject>,
     pub selected_git_status: Option<GitStatus>,
+    /// Resolved environment of the selected project or service
+    selected_env: Vec<(String, String)>,
     // Input dialogs
     input_mode: InputMode,
     run_cmd_input: InputDialog,
@@ -47,9 +97,23 @@ pub struct App {
     clone_path_input: InputDialog,
     repo_selector: RepoSelector,
     scan_selector: RepoSelector,
+    log_selector: RepoSelector,
+    package_selector: RepoSelector,
     // Process management
     pub process_manager: ProcessManager,
     show_logs: bool,
+    /// A past run's log file shown in the logs pane: (title, lines)
+    viewed_log: Option<(String, Vec<String>)>,
+    log_view: LogView,
+    stdin_input: Option<StdinInput>,
+    port_conflict: Option<PortConflict>,
+    /// Ports to run projects on instead of their own, for the next start only
+    port_overrides: HashMap<String, u16>,
+    /// Run pipelines by project name, kept after a failure until dismissed
+    pipelines: HashMap<String, Pipeline>,
+    /// Stops waiting for their processes to exit, with the project whose
+    /// post-stop hooks run once they have
+    stops: Vec<(PendingStop, Option<ProjectEntry>)>,
     // Port scanning
     pub port_info: Vec<PortInfo>,
     last_port_scan: std::time::Instant,
@@ -73,11 +137,16 @@ impl App {
             list_state.select(Some(0));
         }
 
+        let mut process_manager = ProcessManager::attach();
+        process_manager.set_logging(&store.logs);
+
+        let port_info = ports::scan_ports(&store.ports);
         let app = Self {
             store,
             list_state,
             selected_detection: None,
             selected_git_status: None,
+            selected_env: Vec::new(),
             input_mode: InputMode::Normal,
             run_cmd_input: InputDialog::new("Run Command"),
             import_path_input: InputDialog::new("Import Path"),
@@ -85,9 +154,18 @@ impl App {
             clone_path_input: InputDialog::new("Clone to Directory"),
             repo_selector: RepoSelector::new(),
             scan_selector: RepoSelector::new(),
-            process_manager: ProcessManager::new(),
+            log_selector: RepoSelector::with_title("Past Runs"),
+            package_selector: RepoSelector::multi_select("Workspace Packages"),
+            process_manager,
             show_logs: true,
-            port_info: ports::scan_ports(),
+            viewed_log: None,
+            log_view: LogView::new(),
+            stdin_input: None,
+            port_conflict: None,
+            port_overrides: HashMap::new(),
+            pipelines: HashMap::new(),
+            stops: Vec::new(),
+            port_info,
             last_port_scan: std::time::Instant::now(),
             gh_available,
             should_quit: false,
@@ -106,7 +184,7 @@ impl App {
     }
 
     /// Canonicalize a path, stripping the \\?\ prefix that Windows adds.
-    fn clean_path(path: &Path) -> PathBuf {
+    pub fn clean_path(path: &Path) -> PathBuf {
         let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
         #[cfg(windows)]
         {
@@ -126,6 +204,16 @@ impl App {
         self.input_mode = InputMode::ConfirmQuit;
     }
 
+    /// Stop all running processes before quitting, and see the stops already
+    /// under way through. Dev servers owned by the supervisor are left alive.
+    pub fn shutdown(&mut self) {
+        let _ = self.process_manager.stop_all();
+        let freeing = self.port_conflict.take().and_then(|c| c.freeing);
+        for pending in self.stops.drain(..).map(|(pending, _)| pending).chain(freeing) {
+            let _ = self.process_manager.wait_stop(pending);
+        }
+    }
+
     pub fn handle_key(&mut self, key: KeyCode) {
         match self.input_mode {
             InputMode::Normal => self.handle_normal_key(key),
@@ -161,13 +249,12 @@ impl App {
                         repo_url: remote_opt,
                         path,
                         run_command: None,
+                        ..Default::default()
                     });
                     let _ = self.store.save();
 
                     // Select the newly added project
-                    if let Some(idx) = self.store.projects.iter().position(|p| p.name == name) {
-                        self.list_state.select(Some(idx));
-                    }
+                    self.select_project(&name);
                     self.update_selected_details();
                 }
                 if !self.scan_selector.visible {
@@ -215,9 +302,9 @@ impl App {
             InputMode::ClonePath => {
                 if let Some(dir) = self.clone_path_input.handle_key(key) {
                     if !dir.is_empty() {
-                        let path = if dir.starts_with("~/") {
+                        let path = if let Some(rest) = dir.strip_prefix("~/") {
                             if let Some(home) = dirs::home_dir() {
-                                home.join(&dir[2..])
+                                home.join(rest)
                             } else {
                                 std::path::PathBuf::from(&dir)
                             }
@@ -232,6 +319,30 @@ impl App {
                     self.input_mode = InputMode::Normal;
                 }
             }
+            InputMode::SelectLog => {
+                if let Some((_display, path)) = self.log_selector.handle_key(key) {
+                    self.view_log_file(Path::new(&path));
+                }
+                if !self.log_selector.visible {
+                    self.input_mode = InputMode::Normal;
+                }
+            }
+            InputMode::SelectPackages => {
+                if let Some((_display, dir)) = self.package_selector.handle_key(key) {
+                    let dirs = self.package_selector.take_marked(dir);
+                    self.add_workspace_packages(&dirs);
+                }
+                if !self.package_selector.visible {
+                    self.input_mode = InputMode::Normal;
+                }
+            }
+            InputMode::ViewLogs => {
+                if !self.log_view.handle_key(key) {
+                    self.input_mode = InputMode::Normal;
+                }
+            }
+            InputMode::SendInput => self.handle_send_input_key(key),
+            InputMode::PortConflict => self.handle_port_conflict_key(key),
             InputMode::ConfirmQuit => match key {
                 KeyCode::Char('y') | KeyCode::Char('Y') => {
                     self.should_quit = true;
@@ -250,20 +361,73 @@ impl App {
             InputMode::ImportPath => self.import_path_input.value.push_str(text),
             InputMode::SetInstallDir => self.install_dir_input.value.push_str(text),
             InputMode::ClonePath => self.clone_path_input.value.push_str(text),
+            InputMode::ViewLogs => self.log_view.paste(text),
+            InputMode::SendInput => match self.stdin_input {
+                Some(StdinInput { line: Some(ref mut line), .. }) => line.push_str(text),
+                _ => self.send_input(text),
+            },
             _ => {}
         }
     }
 
+    /// Forward a key to the process, or edit the line being typed.
+    fn handle_send_input_key(&mut self, key: KeyCode) {
+        let input = match self.stdin_input {
+            Some(ref mut input) => input,
+            None => {
+                self.input_mode = InputMode::Normal;
+                return;
+            }
+        };
+        let data = match (key, &mut input.line) {
+            (KeyCode::Esc, _) => {
+                self.stdin_input = None;
+                self.input_mode = InputMode::Normal;
+                return;
+            }
+            (KeyCode::Tab, line) => {
+                *line = match line {
+                    Some(_) => None,
+                    None => Some(String::new()),
+                };
+                return;
+            }
+            (KeyCode::Enter, Some(line)) => format!("{}\n", std::mem::take(line)),
+            (KeyCode::Backspace, Some(line)) => {
+                line.pop();
+                return;
+            }
+            (KeyCode::Char(c), Some(line)) => {
+                line.push(c);
+                return;
+            }
+            (_, Some(_)) => return,
+            (key, None) => match key_input(key) {
+                Some(data) => data,
+                None => return,
+            },
+        };
+        self.send_input(&data);
+    }
+
+    fn send_input(&mut self, data: &str) {
+        if let Some(ref mut input) = self.stdin_input {
+            input.error = self
+                .process_manager
+                .send_input(&input.key, data)
+                .err()
+                .map(|e| e.to_string());
+        }
+    }
+
     fn handle_normal_key(&mut self, key: KeyCode) {
         match key {
             KeyCode::Up | KeyCode::Char('k') => self.previous(),
             KeyCode::Down | KeyCode::Char('j') => self.next(),
-            KeyCode::Char('a') => {
-                if self.gh_available {
-                    if let Ok(repos) = gh::list_repos() {
-                        self.repo_selector.show(repos);
-                        self.input_mode = InputMode::SelectRepo;
-                    }
+            KeyCode::Char('a') if self.gh_available => {
+                if let Ok(repos) = gh::list_repos() {
+                    self.repo_selector.show(repos);
+                    self.input_mode = InputMode::SelectRepo;
                 }
             }
             KeyCode::Char('s') => self.scan_for_projects(),
@@ -271,24 +435,22 @@ impl App {
             KeyCode::Char('r') => self.run_selected(),
             KeyCode::Char('x') => self.stop_selected(),
             KeyCode::Char('e') => {
-                if let Some(idx) = self.list_state.selected() {
-                    if let Some(project) = self.store.projects.get(idx) {
-                        if !project.path.is_empty() && Path::new(&project.path).exists() {
-                            let current = project
-                                .run_command
-                                .as_deref()
-                                .or_else(|| {
-                                    self.selected_detection
-                                        .as_ref()
-                                        .and_then(|d| d.run_command.as_deref())
-                                });
-                            match current {
-                                Some(cmd) => self.run_cmd_input.set_hint(&format!("Current: {}", cmd)),
-                                None => self.run_cmd_input.set_hint("Current: not set"),
-                            }
-                            self.run_cmd_input.show();
-                            self.input_mode = InputMode::EditRunCmd;
+                if let Some(project) = self.selected_project() {
+                    if !project.path.is_empty() && Path::new(&project.path).exists() {
+                        let current = match self.selected_service() {
+                            Some(service) => Some(service.command.as_str()),
+                            None => project.run_command.as_deref().or_else(|| {
+                                self.selected_detection
+                                    .as_ref()
+                                    .and_then(|d| d.run_command.as_deref())
+                            }),
+                        };
+                        match current {
+                            Some(cmd) => self.run_cmd_input.set_hint(&format!("Current: {}", cmd)),
+                            None => self.run_cmd_input.set_hint("Current: not set"),
                         }
+                        self.run_cmd_input.show();
+                        self.input_mode = InputMode::EditRunCmd;
                     }
                 }
             }
@@ -311,18 +473,16 @@ impl App {
                 self.input_mode = InputMode::SetInstallDir;
             }
             KeyCode::Char('g') => {
-                if let Some(idx) = self.list_state.selected() {
-                    if let Some(project) = self.store.projects.get(idx) {
-                        let path_empty = project.path.is_empty() || !Path::new(&project.path).exists();
-                        let has_repo = project.repo_url.is_some();
-                        if path_empty && has_repo {
-                            if let Some(install_dir) = self.store.get_install_dir() {
-                                self.clone_selected_to(install_dir);
-                            } else {
-                                self.clone_path_input.set_hint("Enter directory to clone into");
-                                self.clone_path_input.show();
-                                self.input_mode = InputMode::ClonePath;
-                            }
+                if let Some(project) = self.selected_project() {
+                    let path_empty = project.path.is_empty() || !Path::new(&project.path).exists();
+                    let has_repo = project.repo_url.is_some();
+                    if path_empty && has_repo {
+                        if let Some(install_dir) = self.store.get_install_dir() {
+                            self.clone_selected_to(install_dir);
+                        } else {
+                            self.clone_path_input.set_hint("Enter directory to clone into");
+                            self.clone_path_input.show();
+                            self.input_mode = InputMode::ClonePath;
                         }
                     }
                 }
@@ -333,6 +493,34 @@ impl App {
                     self.update_downloader = Some(UpdateDownloader::start(info));
                 }
             }
+            KeyCode::Char('l') => self.show_past_runs(),
+            KeyCode::Char('w') => self.show_workspace_packages(),
+            KeyCode::Char('t') => {
+                if let Some(key) = self.selected_key().filter(|k| self.process_manager.is_running(k)) {
+                    self.viewed_log = None;
+                    self.stdin_input = Some(StdinInput {
+                        key,
+                        line: None,
+                        error: None,
+                    });
+                    self.input_mode = InputMode::SendInput;
+                }
+            }
+            KeyCode::Char('v') => {
+                if let Some((label, _)) = self.current_log() {
+                    self.log_view.show(&label);
+                    self.input_mode = InputMode::ViewLogs;
+                }
+            }
+            KeyCode::Esc => {
+                self.viewed_log = None;
+                // Dismiss the report of a finished run
+                if let Some(name) = self.selected_project().map(|p| p.name.clone()) {
+                    if self.pipelines.get(&name).is_some_and(|p| !p.is_running()) {
+                        self.pipelines.remove(&name);
+                    }
+                }
+            }
             KeyCode::F(5) => self.full_refresh(),
             KeyCode::Enter => self.update_selected_details(),
             _ => {}
@@ -367,13 +555,12 @@ impl App {
             repo_url,
             path,
             run_command: None,
+            ..Default::default()
         });
         let _ = self.store.save();
 
         // Select the new project
-        if let Some(idx) = self.store.projects.iter().position(|p| p.name == name) {
-            self.list_state.select(Some(idx));
-        }
+        self.select_project(name);
         self.update_selected_details();
     }
 
@@ -400,9 +587,9 @@ impl App {
     }
 
     fn clone_selected_to(&mut self, base_dir: std::path::PathBuf) {
-        if let Some(idx) = self.list_state.selected() {
+        {
             let (name, repo_url) = {
-                let project = match self.store.projects.get(idx) {
+                let project = match self.selected_project() {
                     Some(p) => p,
                     None => return,
                 };
@@ -429,13 +616,16 @@ impl App {
     }
 
     fn set_run_command(&mut self, cmd: Option<&str>) {
-        if let Some(idx) = self.list_state.selected() {
-            let name = match self.store.projects.get(idx) {
-                Some(p) => p.name.clone(),
-                None => return,
-            };
-            if let Some(project) = self.store.get_mut(&name) {
-                project.run_command = cmd.map(|s| s.to_string());
+        if let Some(row) = self.selected_row() {
+            let project = &mut self.store.projects[row.project];
+            match row.service {
+                // A service always needs a command; clearing it leaves it unchanged
+                Some(svc) => {
+                    if let Some(cmd) = cmd {
+                        project.services[svc].command = cmd.to_string();
+                    }
+                }
+                None => project.run_command = cmd.map(|s| s.to_string()),
             }
             let _ = self.store.save();
             self.update_selected_details();
@@ -471,13 +661,12 @@ impl App {
             repo_url: remote_url,
             path: path_str.to_string(),
             run_command: None,
+            ..Default::default()
         });
         let _ = self.store.save();
 
         // Select the new project
-        if let Some(idx) = self.store.projects.iter().position(|p| p.name == name) {
-            self.list_state.select(Some(idx));
-        }
+        self.select_project(&name);
         self.update_selected_details();
     }
 
@@ -518,7 +707,7 @@ impl App {
         self.git_worker.invalidate_all();
 
         // Refresh port scan
-        self.port_info = ports::scan_ports();
+        self.port_info = ports::scan_ports(&self.store.ports);
         self.last_port_scan = std::time::Instant::now();
 
         // Update selected details
@@ -526,29 +715,39 @@ impl App {
     }
 
     fn delete_selected(&mut self) {
-        if let Some(idx) = self.list_state.selected() {
-            let name = match self.store.projects.get(idx) {
-                Some(p) => p.name.clone(),
-                None => return,
-            };
-            self.store.remove(&name);
+        if let (Some(idx), Some(row)) = (self.list_state.selected(), self.selected_row()) {
+            let project = &mut self.store.projects[row.project];
+            match row.service {
+                // On a service row, remove just that service
+                Some(svc) => {
+                    let key = project.service_key(&project.services[svc].name);
+                    project.services.remove(svc);
+                    let pending = self.process_manager.stop_in_background(&[key]);
+                    self.stops.push((pending, None));
+                }
+                None => {
+                    let name = project.name.clone();
+                    if let Some(mut pipeline) = self.pipelines.remove(&name) {
+                        pipeline.cancel();
+                    }
+                    self.store.remove(&name);
+                }
+            }
             let _ = self.store.save();
 
-            if self.store.projects.is_empty() {
+            let rows = self.list_rows().len();
+            if rows == 0 {
                 self.list_state.select(None);
-            } else if idx >= self.store.projects.len() {
-                self.list_state.select(Some(self.store.projects.len() - 1));
+            } else if idx >= rows {
+                self.list_state.select(Some(rows - 1));
             }
             self.update_selected_details();
         }
     }
 
     fn run_selected(&mut self) {
-        if let Some(idx) = self.list_state.selected() {
-            let project = match self.store.projects.get(idx) {
-                Some(p) => p,
-                None => return,
-            };
+        if let Some(row) = self.selected_row() {
+            let project = self.store.projects[row.project].clone();
 
             if project.path.is_empty() {
                 return;
@@ -559,102 +758,289 @@ impl App {
                 return;
             }
 
-            let project_name = project.name.clone();
-            let run_command_override = project.run_command.clone();
+            // On a service row, run just that service
+            let service = row.service.map(|svc| project.services[svc].clone());
+            let keys = match service {
+                Some(ref service) => vec![project.service_key(&service.name)],
+                None => project.process_keys(),
+            };
 
-            // Git fetch before running (blocking)
-            self.git_fetch(&path_buf);
+            // Starting again before the old processes are gone would find
+            // their ports taken
+            if keys.iter().any(|key| self.is_stopping(key)) {
+                return;
+            }
 
-            // Install dependencies for JS projects before starting dev server
-            if self.is_js_project() {
-                self.install_node_modules(&path_buf);
+            // One run at a time per project
+            if self.pipelines.get(&project.name).is_some_and(|p| p.is_running()) {
+                return;
             }
 
-            // Spawn a new terminal with claude
-            self.spawn_terminal_with_claude(&path_buf, &project_name);
+            // Detection runs in the background and may not be in yet
+            let detection = self
+                .selected_detection
+                .clone()
+                .or_else(|| detect::detect(&path_buf).ok());
+
+            // A pinned port that something else holds needs a decision first
+            if !self.process_manager.is_running(&keys[0]) {
+                let holder = match service {
+                    Some(ref service) => launch::service_port_holder(service),
+                    None => launch::pinned_port_holder(&project, detection.as_ref()),
+                };
+                if let Some(holder) = holder {
+                    let owner = self
+                        .process_manager
+                        .running_projects()
+                        .into_iter()
+                        .find(|key| self.process_manager.get_port(key) == Some(holder.port));
+                    // A service's command decides its port, so it can't move
+                    let fallback = match service {
+                        Some(_) => None,
+                        None => self
+                            .store
+                            .ports
+                            .find_available(&self.store.ports_reserved_by_others(&project.name)),
+                    };
+                    self.port_conflict = Some(PortConflict {
+                        project: project.name.clone(),
+                        service: service.map(|s| s.name),
+                        holder,
+                        owner,
+                        fallback,
+                        error: None,
+                        freeing: None,
+                    });
+                    self.input_mode = InputMode::PortConflict;
+                    return;
+                }
+            } else if service.is_some() {
+                return;
+            }
 
-            // Also start any dev server in background if not already running
-            if !self.process_manager.is_running(&project_name) {
-                let cmd = run_command_override
-                    .or_else(|| self.selected_detection.as_ref().and_then(|d| d.run_command.clone()));
+            self.start_run(&project, service.as_ref().map(|s| s.name.as_str()), detection.as_ref());
+        }
+    }
 
-                if let Some(cmd) = cmd {
-                    // For JavaScript projects, find an available port
-                    let port = if self.is_js_project() {
-                        ports::find_available_port()
-                    } else {
-                        None
-                    };
+    /// Hooks, the terminal, then everything starts; or with `service`, the
+    /// hooks and that service. Commands run in the background; see
+    /// `advance_pipelines`.
+    fn start_run(
+        &mut self,
+        project: &ProjectEntry,
+        service: Option<&str>,
+        detection: Option<&detect::DetectedProject>,
+    ) {
+        let steps = match service {
+            Some(service) => pipeline::service_run_steps(project, service, detection),
+            None => pipeline::run_steps(project, detection),
+        };
+        let path = PathBuf::from(&project.path);
+        self.pipelines
+            .insert(project.name.clone(), Pipeline::new(Kind::Run, &path, steps));
+    }
 
-                    let _ = self.process_manager.start_with_port(&project_name, &path_buf, &cmd, port);
+    /// `f` runs on the fallback port this time, `k` frees the pinned port by
+    /// stopping whatever holds it, `Esc` gives up on the run.
+    fn handle_port_conflict_key(&mut self, key: KeyCode) {
+        let conflict = match self.port_conflict {
+            Some(ref mut conflict) => conflict,
+            None => {
+                self.input_mode = InputMode::Normal;
+                return;
+            }
+        };
+        match key {
+            // The holder keeps stopping; only the run is given up on
+            KeyCode::Esc => {
+                if let Some(pending) = conflict.freeing.take() {
+                    self.stops.push((pending, None));
+                }
+                self.port_conflict = None;
+                self.input_mode = InputMode::Normal;
+            }
+            _ if conflict.freeing.is_some() => {}
+            KeyCode::Char('f') => {
+                if let Some(port) = conflict.fallback {
+                    self.port_overrides.insert(conflict.project.clone(), port);
+                    self.resolve_port_conflict();
                 }
             }
+            KeyCode::Char('k') => {
+                conflict.error = None;
+                conflict.freeing = match (&conflict.owner, conflict.holder.pid) {
+                    (Some(owner), _) => Some(self.process_manager.stop_in_background(std::slice::from_ref(owner))),
+                    (None, Some(pid)) => Some(PendingStop::kill(pid)),
+                    (None, None) => {
+                        conflict.error = Some("cannot tell which process holds the port".to_string());
+                        None
+                    }
+                };
+            }
+            _ => {}
         }
     }
 
-    fn git_fetch(&self, path: &Path) {
-        use std::process::Command;
-
-        // Run git fetch and wait for completion
-        let _ = Command::new("git")
-            .args(["fetch", "--all", "--prune"])
-            .current_dir(path)
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .status(); // .status() waits for completion
+    /// Close the port conflict dialog and go ahead with the run.
+    fn resolve_port_conflict(&mut self) {
+        let conflict = match self.port_conflict.take() {
+            Some(conflict) => conflict,
+            None => return,
+        };
+        self.input_mode = InputMode::Normal;
+        if let Some(project) = self.store.get(&conflict.project).cloned() {
+            let detection = detect::detect(Path::new(&project.path)).ok();
+            self.start_run(&project, conflict.service.as_deref(), detection.as_ref());
+        }
     }
 
-    fn install_node_modules(&self, path: &Path) {
-        use std::process::Command;
+    /// Pick up stops that are done: run the post-stop hooks of their project,
+    /// or the run that was waiting for a port to be freed.
+    fn advance_stops(&mut self) {
+        let mut i = 0;
+        while i < self.stops.len() {
+            let result = match self.process_manager.poll_stop(&self.stops[i].0) {
+                Some(result) => result,
+                None => {
+                    i += 1;
+                    continue;
+                }
+            };
+            let (_, project) = self.stops.remove(i);
+            if let (Ok(()), Some(project)) = (result, project) {
+                let steps = pipeline::stop_steps(&project);
+                if !steps.is_empty() {
+                    let path = PathBuf::from(&project.path);
+                    self.pipelines
+                        .insert(project.name.clone(), Pipeline::new(Kind::Stop, &path, steps));
+                }
+            }
+        }
 
-        // Get the package manager from detection
-        let pm = self
-            .selected_detection
-            .as_ref()
-            .and_then(|d| d.package_manager)
-            .unwrap_or(detect::PackageManager::Npm);
-
-        let install_cmd = match pm {
-            detect::PackageManager::Pnpm => "pnpm",
-            detect::PackageManager::Yarn => "yarn",
-            detect::PackageManager::Bun => "bun",
-            detect::PackageManager::Npm => "npm",
-            _ => return, // Not a JS package manager
+        let freed = match self.port_conflict {
+            Some(PortConflict {
+                freeing: Some(ref pending),
+                ..
+            }) => self.process_manager.poll_stop(pending),
+            _ => None,
         };
+        match freed {
+            Some(Ok(())) => self.resolve_port_conflict(),
+            Some(Err(e)) => {
+                if let Some(ref mut conflict) = self.port_conflict {
+                    conflict.freeing = None;
+                    conflict.error = Some(e.to_string());
+                }
+            }
+            None => {}
+        }
+    }
 
-        // Run install and wait for completion
-        // On Windows, npm/pnpm/yarn/bun are .cmd files, must run through cmd.exe
-        #[cfg(windows)]
-        {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            let _ = Command::new("cmd.exe")
-                .args(["/c", install_cmd, "install"])
-                .current_dir(path)
-                .stdin(std::process::Stdio::null())
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::null())
-                .creation_flags(CREATE_NO_WINDOW)
-                .status();
-        }
-
-        #[cfg(not(windows))]
-        let _ = Command::new(install_cmd)
-            .arg("install")
-            .current_dir(path)
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .status();
+    /// The process is being stopped, and hasn't exited yet.
+    fn is_stopping(&self, key: &str) -> bool {
+        let freeing = self.port_conflict.as_ref().and_then(|c| c.freeing.as_ref());
+        self.stops
+            .iter()
+            .map(|(pending, _)| pending)
+            .chain(freeing)
+            .any(|pending| pending.names().iter().any(|name| name == key))
     }
 
-    fn is_js_project(&self) -> bool {
-        self.selected_detection
-            .as_ref()
-            .map(|d| d.project_type == detect::ProjectType::JavaScript)
-            .unwrap_or(false)
+    /// Pick up progress of the pipelines, and perform the steps that need the
+    /// app itself as they come up.
+    fn advance_pipelines(&mut self) {
+        let names: Vec<String> = self.pipelines.keys().cloned().collect();
+        for name in names {
+            let project = match self.store.get(&name) {
+                Some(p) => p.clone(),
+                None => {
+                    self.pipelines.remove(&name);
+                    continue;
+                }
+            };
+            let path = PathBuf::from(&project.path);
+
+            loop {
+                let step = match self.pipelines.get_mut(&name) {
+                    Some(pipeline) => pipeline.advance(),
+                    None => None,
+                };
+                let (i, action) = match step {
+                    Some(step) => step,
+                    None => break,
+                };
+
+                let result = match action {
+                    Action::OpenTerminal => self
+                        .spawn_terminal_with_claude(&path, &name)
+                        .map_err(|e| e.to_string()),
+                    Action::StartProcesses => {
+                        let mut project = project.clone();
+                        if let Some(port) = self.port_overrides.remove(&name) {
+                            project.port = Some(port);
+                        }
+                        self.start_processes(&project, &path)
+                    }
+                    Action::StartService(ref service) => self.start_service(&project, service),
+                    Action::Command(_) => Ok(()),
+                };
+                if let Some(pipeline) = self.pipelines.get_mut(&name) {
+                    pipeline.finish(i, result);
+                }
+            }
+
+            // A clean run has nothing left to show
+            let done = self
+                .pipelines
+                .get(&name)
+                .is_some_and(|p| !p.is_running() && p.failure().is_none() && p.warnings().next().is_none());
+            if done {
+                self.pipelines.remove(&name);
+            }
+        }
+    }
+
+    /// Start one service, unless it is already running.
+    fn start_service(&mut self, project: &ProjectEntry, service: &str) -> Result<(), String> {
+        let target = match project.service(service) {
+            Some(service) => launch::service(project, service),
+            None => return Err(format!("{} has no service named {}", project.name, service)),
+        };
+        if self.process_manager.is_running(&target.key) {
+            return Ok(());
+        }
+        self.process_manager
+            .launch(&target.key, &target.spec)
+            .map_err(|e| e.to_string())
     }
 
-    fn spawn_terminal_with_claude(&mut self, path: &Path, project_name: &str) {
+    /// Start the dev server and services that aren't already running.
+    fn start_processes(&mut self, project: &ProjectEntry, path: &Path) -> Result<(), String> {
+        // Detect again: installing may have changed what the project looks like
+        let detection = detect::detect(path).ok();
+        let mut errors = Vec::new();
+        for target in launch::all(project, detection.as_ref(), &self.store) {
+            if self.process_manager.is_running(&target.key) {
+                continue;
+            }
+            if let Err(e) = self.process_manager.launch(&target.key, &target.spec) {
+                errors.push(format!("{}: {}", target.key, e));
+                continue;
+            }
+            // Keep the dev server's port for next time
+            if let (true, Some(port)) = (target.key == project.name, target.spec.port) {
+                if self.store.remember_port(&project.name, port) {
+                    let _ = self.store.save();
+                }
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors.join("; ")),
+        }
+    }
+
+    fn spawn_terminal_with_claude(&mut self, path: &Path, project_name: &str) -> anyhow::Result<()> {
         use std::process::Command;
 
         let path_str = path.to_string_lossy().to_string();
@@ -717,8 +1103,9 @@ impl App {
                 None
             };
 
-            if let Some(child) = child {
-                self.process_manager.set_claude_pid(project_name, child.id());
+            match child {
+                Some(child) => self.process_manager.set_claude_pid(project_name, child.id()),
+                None => anyhow::bail!("No supported terminal emulator found"),
             }
         }
 
@@ -733,9 +1120,9 @@ impl App {
                 end tell"#,
                 path_str
             );
-            let _ = Command::new("osascript")
+            Command::new("osascript")
                 .args(["-e", &script])
-                .spawn();
+                .spawn()?;
         }
 
         #[cfg(target_os = "windows")]
@@ -791,17 +1178,147 @@ impl App {
                     .ok()
             };
 
-            if let Some(child) = child {
-                self.process_manager.set_claude_pid(project_name, child.id());
+            match child {
+                Some(child) => self.process_manager.set_claude_pid(project_name, child.id()),
+                None => anyhow::bail!("No supported terminal emulator found"),
             }
         }
+
+        Ok(())
+    }
+
+    /// List the log files of the selected project or service to pick one from.
+    fn show_past_runs(&mut self) {
+        let key = match self.selected_key() {
+            Some(k) => k,
+            None => return,
+        };
+        let runs: Vec<(String, String)> = logs::list(&key)
+            .into_iter()
+            .map(|entry| {
+                let name = entry
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let display = format!("{}  ({:.1} KB)", name, entry.size as f64 / 1024.0);
+                (display, entry.path.to_string_lossy().to_string())
+            })
+            .collect();
+        if runs.is_empty() {
+            self.viewed_log = Some((key, vec!["No log files yet".to_string()]));
+            return;
+        }
+        self.log_selector.show(runs);
+        self.input_mode = InputMode::SelectLog;
+    }
+
+    /// Offer the runnable workspace packages that aren't services yet.
+    fn show_workspace_packages(&mut self) {
+        let (project, detection) = match (self.selected_project(), self.selected_detection.as_ref()) {
+            (Some(project), Some(detection)) => (project, detection),
+            _ => return,
+        };
+        let packages: Vec<(String, String)> = detection
+            .packages
+            .iter()
+            .filter(|pkg| !project.services.iter().any(|s| s.cwd.as_deref() == Some(pkg.dir.as_str())))
+            .filter_map(|pkg| {
+                let command = pkg.run_command.as_ref()?;
+                let display = format!("{:<20} {:<18} {}", pkg.name, pkg.dir, command);
+                Some((display, pkg.dir.clone()))
+            })
+            .collect();
+        if packages.is_empty() {
+            return;
+        }
+        self.package_selector.show(packages);
+        self.input_mode = InputMode::SelectPackages;
+    }
+
+    /// Add workspace packages to the selected project as services, each run
+    /// from its own directory on a port of its own.
+    fn add_workspace_packages(&mut self, dirs: &[String]) {
+        let packages: Vec<WorkspacePackage> = match self.selected_detection {
+            Some(ref detection) => detection
+                .packages
+                .iter()
+                .filter(|pkg| dirs.contains(&pkg.dir))
+                .cloned()
+                .collect(),
+            None => return,
+        };
+        let row = match self.selected_row() {
+            Some(row) => row,
+            None => return,
+        };
+        // The same reservations the dev servers are assigned around, plus
+        // this project's own dev server and services
+        let mut taken = self
+            .store
+            .ports_reserved_by_others(&self.store.projects[row.project].name);
+        taken.extend(self.store.projects[row.project].reserved_ports());
+        let port_settings = self.store.ports.clone();
+        let project = &mut self.store.projects[row.project];
+
+        for pkg in packages {
+            let command = match pkg.run_command {
+                Some(ref command) => command.clone(),
+                None => continue,
+            };
+            // Scoped packages can share a name, e.g. `@a/web` and `@b/web`
+            let name = project.free_service_name(&pkg.service_name(), &pkg.dir.replace('/', "-"));
+            let port = port_settings.find_available(&taken);
+            taken.extend(port);
+            // Services keep their port, so it goes into the command as the
+            // package's framework expects; `PORT` is always exported
+            let mut service = ServiceEntry {
+                name,
+                command,
+                cwd: Some(pkg.dir.clone()),
+                port,
+                ..Default::default()
+            };
+            if let Some(port) = port {
+                let (command, extra) = pkg.port_style.apply(&service.command, port);
+                service.command = command;
+                service.env.extend(extra.into_iter().filter(|(key, _)| key != "PORT"));
+            }
+            project.services.push(service);
+        }
+        let _ = self.store.save();
+        self.update_selected_details();
+    }
+
+    fn view_log_file(&mut self, path: &Path) {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let lines = logs::read_tail(path, 1000)
+            .unwrap_or_else(|e| vec![format!("Cannot read {}: {}", path.display(), e)]);
+        self.viewed_log = Some((name, lines));
     }
 
     fn stop_selected(&mut self) {
-        if let Some(project) = self.selected_project() {
-            let name = project.name.clone();
-            let _ = self.process_manager.stop(&name);
+        let (keys, project) = match (self.selected_project(), self.selected_key()) {
+            // A project row stops the dev server, every service and the Claude
+            // terminal, then runs the project's post-stop hooks
+            (Some(project), Some(key)) if key == project.name => {
+                (project.process_keys(), Some(project.clone()))
+            }
+            (_, Some(key)) => (vec![key], None),
+            _ => return,
+        };
+        // A run still fetching or installing would otherwise start it again
+        if let Some(pipeline) = project.as_ref().and_then(|p| self.pipelines.get_mut(&p.name)) {
+            pipeline.cancel();
+        }
+        if keys.iter().any(|key| self.is_stopping(key)) {
+            return;
         }
+        let pending = self.process_manager.stop_in_background(&keys);
+        self.stops.push((pending, project));
     }
 
     pub fn is_input_mode(&self) -> bool {
@@ -809,11 +1326,12 @@ impl App {
     }
 
     fn next(&mut self) {
-        if self.store.projects.is_empty() {
+        let rows = self.list_rows().len();
+        if rows == 0 {
             return;
         }
         let i = match self.list_state.selected() {
-            Some(i) => (i + 1) % self.store.projects.len(),
+            Some(i) => (i + 1) % rows,
             None => 0,
         };
         self.list_state.select(Some(i));
@@ -821,13 +1339,14 @@ impl App {
     }
 
     fn previous(&mut self) {
-        if self.store.projects.is_empty() {
+        let rows = self.list_rows().len();
+        if rows == 0 {
             return;
         }
         let i = match self.list_state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.store.projects.len() - 1
+                    rows - 1
                 } else {
                     i - 1
                 }
@@ -839,34 +1358,84 @@ impl App {
     }
 
     fn update_selected_details(&mut self) {
-        if let Some(idx) = self.list_state.selected() {
-            if let Some(project) = self.store.projects.get(idx) {
-                if !project.path.is_empty() && Path::new(&project.path).exists() {
-                    // Use cached data immediately
-                    self.selected_detection = self.git_worker.get_detection(&project.path).cloned();
-                    self.selected_git_status = self.git_worker.get_git_status(&project.path).cloned();
-
-                    // Request fresh data in background if stale
-                    if self.git_worker.is_stale(&project.path) {
-                        self.git_worker.request(&project.path);
-                    }
-                    return;
+        self.viewed_log = None;
+        self.selected_env = match (self.selected_project(), self.selected_service()) {
+            (Some(project), Some(service)) => launch::service(project, service).spec.env,
+            (Some(project), None) => project.process_env(),
+            _ => Vec::new(),
+        };
+
+        if let Some(path) = self.selected_project().map(|p| p.path.clone()) {
+            if !path.is_empty() && Path::new(&path).exists() {
+                // Use cached data immediately
+                self.selected_detection = self.git_worker.get_detection(&path).cloned();
+                self.selected_git_status = self.git_worker.get_git_status(&path).cloned();
+
+                // Request fresh data in background if stale
+                if self.git_worker.is_stale(&path) {
+                    self.git_worker.request(&path);
                 }
+                return;
             }
         }
         self.selected_detection = None;
         self.selected_git_status = None;
     }
 
-    fn selected_project(&self) -> Option<&ProjectEntry> {
+    /// Projects in display order, each followed by its services.
+    fn list_rows(&self) -> Vec<ListRow> {
+        let mut rows = Vec::new();
+        for (i, project) in self.store.projects.iter().enumerate() {
+            rows.push(ListRow {
+                project: i,
+                service: None,
+            });
+            rows.extend((0..project.services.len()).map(|s| ListRow {
+                project: i,
+                service: Some(s),
+            }));
+        }
+        rows
+    }
+
+    fn selected_row(&self) -> Option<ListRow> {
         self.list_state
             .selected()
-            .and_then(|i| self.store.projects.get(i))
+            .and_then(|i| self.list_rows().get(i).copied())
+    }
+
+    /// The selected project, or the project owning the selected service.
+    fn selected_project(&self) -> Option<&ProjectEntry> {
+        self.selected_row()
+            .and_then(|row| self.store.projects.get(row.project))
+    }
+
+    fn selected_service(&self) -> Option<&ServiceEntry> {
+        let row = self.selected_row()?;
+        self.store.projects.get(row.project)?.services.get(row.service?)
+    }
+
+    /// Process key of the selected row: the project name, or `project/service`.
+    fn selected_key(&self) -> Option<String> {
+        let project = self.selected_project()?;
+        Some(match self.selected_service() {
+            Some(service) => project.service_key(&service.name),
+            None => project.name.clone(),
+        })
+    }
+
+    fn select_project(&mut self, name: &str) {
+        let idx = self.list_rows().iter().position(|row| {
+            row.service.is_none() && self.store.projects[row.project].name == name
+        });
+        if let Some(idx) = idx {
+            self.list_state.select(Some(idx));
+        }
     }
 
     fn maybe_refresh_ports(&mut self) {
         if self.last_port_scan.elapsed() > std::time::Duration::from_secs(30) {
-            self.port_info = ports::scan_ports();
+            self.port_info = ports::scan_ports(&self.store.ports);
             self.last_port_scan = std::time::Instant::now();
         }
     }
@@ -874,12 +1443,10 @@ impl App {
     pub fn render(&mut self, frame: &mut Frame) {
         // Poll for background git status results
         if self.git_worker.poll() {
-            if let Some(idx) = self.list_state.selected() {
-                if let Some(project) = self.store.projects.get(idx) {
-                    if !project.path.is_empty() {
-                        self.selected_detection = self.git_worker.get_detection(&project.path).cloned();
-                        self.selected_git_status = self.git_worker.get_git_status(&project.path).cloned();
-                    }
+            if let Some(path) = self.selected_project().map(|p| p.path.clone()) {
+                if !path.is_empty() {
+                    self.selected_detection = self.git_worker.get_detection(&path).cloned();
+                    self.selected_git_status = self.git_worker.get_git_status(&path).cloned();
                 }
             }
         }
@@ -903,9 +1470,29 @@ impl App {
         }
 
         self.process_manager.reap_dead();
+        self.advance_stops();
+        self.advance_pipelines();
         self.maybe_refresh_ports();
 
-        let has_running = !self.process_manager.running_projects().is_empty();
+        if self.log_view.visible {
+            // Borders, plus the viewer's footer line
+            let area = frame.area();
+            self.process_manager
+                .resize_ptys(area.width.saturating_sub(2), area.height.saturating_sub(3));
+            let lines = self.current_log().map(|(_, lines)| lines).unwrap_or_default();
+            self.log_view.set_lines(lines);
+            self.log_view.render(frame, frame.area());
+            return;
+        }
+
+        let has_running = !self.process_manager.running_projects().is_empty()
+            || !self.pipelines.is_empty()
+            || !self.stops.is_empty()
+            || !self.process_manager.exited().is_empty()
+            || self.viewed_log.is_some()
+            || self
+                .selected_key()
+                .is_some_and(|key| self.process_manager.last_run(&key).is_some());
         let help_height = self.help_bar_height(frame.area().width);
 
         let main_chunks = if has_running && self.show_logs {
@@ -954,6 +1541,12 @@ impl App {
         self.clone_path_input.render(frame, area);
         self.repo_selector.render(frame, area);
         self.scan_selector.render(frame, area);
+        self.log_selector.render(frame, area);
+        self.package_selector.render(frame, area);
+
+        if let Some(ref conflict) = self.port_conflict {
+            self.render_port_conflict(frame, area, conflict);
+        }
 
         // Render quit confirmation dialog
         if self.input_mode == InputMode::ConfirmQuit {
@@ -961,6 +1554,68 @@ impl App {
         }
     }
 
+    fn render_port_conflict(&self, frame: &mut Frame, area: Rect, conflict: &PortConflict) {
+        use ratatui::widgets::{Clear, Wrap};
+
+        let width = 64.min(area.width.saturating_sub(4));
+        let height = 7;
+        let x = (area.width.saturating_sub(width)) / 2;
+        let y = (area.height.saturating_sub(height)) / 2;
+        let dialog_area = Rect::new(x, y, width, height);
+
+        frame.render_widget(Clear, dialog_area);
+
+        let holder = match conflict.owner {
+            Some(ref owner) => owner.clone(),
+            None => conflict.holder.describe(),
+        };
+        let mut options = Vec::new();
+        if let Some(port) = conflict.fallback {
+            options.push(Span::styled(format!("[f] run on {}   ", port), theme::label()));
+        }
+        if conflict.owner.is_some() || conflict.holder.pid.is_some() {
+            options.push(Span::styled("[k] stop it and run   ", theme::label()));
+        }
+        options.push(Span::styled("Esc cancel", theme::label()));
+
+        let mut lines = vec![
+            Line::from(match conflict.service {
+                Some(ref service) => format!(
+                    "Port {} is used by {} but held by {}.",
+                    conflict.holder.port,
+                    self.store.get(&conflict.project).map(|p| p.service_key(service)).unwrap_or_default(),
+                    holder
+                ),
+                None => format!(
+                    "Port {} is pinned for {} but held by {}.",
+                    conflict.holder.port, conflict.project, holder
+                ),
+            }),
+            Line::from(""),
+            Line::from(options),
+        ];
+        if conflict.freeing.is_some() {
+            lines.push(Line::from(Span::styled(format!("Stopping {}...", holder), theme::label())));
+        } else if let Some(ref error) = conflict.error {
+            lines.push(Line::from(Span::styled(
+                format!("Cannot stop it: {}", error),
+                Style::default().fg(theme::DANGER),
+            )));
+        }
+
+        let text = Paragraph::new(lines)
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Port Taken ")
+                    .border_style(Style::default().fg(theme::WARNING)),
+            );
+
+        frame.render_widget(text, dialog_area);
+    }
+
     fn render_quit_dialog(&self, frame: &mut Frame, area: Rect) {
         use ratatui::widgets::Clear;
 
@@ -972,7 +1627,15 @@ impl App {
 
         frame.render_widget(Clear, dialog_area);
 
-        let text = Paragraph::new("Quit? (y/n)")
+        // Drawn once more while running processes are stopped on the way out
+        let message = if self.should_quit {
+            "Stopping..."
+        } else if self.process_manager.is_attached() && !self.process_manager.running_projects().is_empty() {
+            "Quit? Dev servers keep running (y/n)"
+        } else {
+            "Quit? (y/n)"
+        };
+        let text = Paragraph::new(message)
             .style(Style::default().fg(Color::White))
             .block(
                 Block::default()
@@ -985,35 +1648,91 @@ impl App {
         frame.render_widget(text, dialog_area);
     }
 
+    /// What the logs show: an opened log file, else the selected process's
+    /// output, else how its last run ended. Returns a label and the lines.
+    fn current_log(&self) -> Option<(String, Vec<String>)> {
+        if let Some((ref file, ref lines)) = self.viewed_log {
+            return Some((file.clone(), lines.clone()));
+        }
+        let name = self.selected_key()?;
+        // Crashed processes keep their output, so the crash can be read
+        if self.process_manager.is_running(&name) || self.process_manager.exit_info(&name).is_some() {
+            let output = self.process_manager.get_output(&name);
+            return Some((name, output));
+        }
+        // Keep showing how the last run ended once it is gone
+        let run = self.process_manager.last_run(&name)?;
+        Some((format!("{}: {}", name, run.summary()), run.tail))
+    }
+
     fn render_logs(&mut self, frame: &mut Frame, area: Rect) {
-        let project_name = self.selected_project().map(|p| p.name.clone());
+        // A run in progress, or one that failed, takes over the pane
+        let pipeline = self
+            .selected_project()
+            .and_then(|p| Some((p.name.clone(), self.pipelines.get(&p.name)?)));
+        if let Some((name, pipeline)) = pipeline {
+            if pipeline.is_running() || pipeline.failure().is_some() {
+                let height = (area.height as usize).saturating_sub(2);
+                let para = Paragraph::new(pipeline_lines(pipeline, height)).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(pipeline_title(&name, pipeline))
+                        .title_style(theme::accent_title())
+                        .border_style(theme::inactive_border()),
+                );
+                frame.render_widget(para, area);
+                return;
+            }
+        }
 
-        let (title, lines) = if let Some(ref name) = project_name {
-            if self.process_manager.is_running(name) {
-                let output = self.process_manager.get_output(name);
-                let title = format!(" Logs ({}) ", name);
-                (title, output)
-            } else {
+        let (title, lines) = match self.current_log() {
+            Some((file, lines)) if self.viewed_log.is_some() => {
+                (format!(" Log file {} (Esc to close) ", file), lines)
+            }
+            Some((label, lines)) => (format!(" Logs ({}) ", label), lines),
+            None if self.selected_key().is_some() => {
                 (" Logs ".to_string(), vec!["No process running".to_string()])
             }
-        } else {
-            (" Logs ".to_string(), vec!["Select a project".to_string()])
+            None => (" Logs ".to_string(), vec!["Select a project".to_string()]),
         };
 
+        // Processes in a pseudo-terminal lay out their output for this pane
+        self.process_manager
+            .resize_ptys(area.width.saturating_sub(2), area.height.saturating_sub(2));
+
+        // While typing to the process, its prompt takes the last line
+        let input = self
+            .stdin_input
+            .as_ref()
+            .filter(|_| self.input_mode == InputMode::SendInput);
+
+        // Steps of the last run that failed without stopping it
+        let mut visible_lines: Vec<Line> = self
+            .selected_project()
+            .and_then(|p| self.pipelines.get(&p.name))
+            .map(|p| p.warnings().map(warning_line).collect())
+            .unwrap_or_default();
+
         // Show last N lines that fit
-        let available_lines = (area.height as usize).saturating_sub(2);
+        let available_lines = (area.height as usize)
+            .saturating_sub(2 + input.is_some() as usize + visible_lines.len());
         let start = lines.len().saturating_sub(available_lines);
-        let visible_lines: Vec<Line> = lines[start..]
-            .iter()
-            .map(|s| Line::from(s.as_str()))
-            .collect();
+        visible_lines.extend(lines[start..].iter().map(|s| ansi::to_line(s)));
+
+        if let Some(input) = input {
+            visible_lines.push(stdin_prompt(input));
+        }
 
+        let border = match input {
+            Some(_) => theme::active_border(),
+            None => theme::inactive_border(),
+        };
         let para = Paragraph::new(visible_lines).block(
             Block::default()
                 .borders(Borders::ALL)
                 .title(title)
                 .title_style(theme::accent_title())
-                .border_style(theme::inactive_border()),
+                .border_style(border),
         );
         frame.render_widget(para, area);
     }
@@ -1024,6 +1743,11 @@ impl App {
 
         let mut spans: Vec<Span> = vec![];
 
+        if self.process_manager.is_attached() {
+            spans.push(Span::styled(" supervised", Style::default().fg(theme::ACCENT)));
+            spans.push(Span::styled(" \u{2502}", theme::label()));
+        }
+
         if running_count > 0 {
             spans.push(Span::styled(
                 format!(" {} running", running_count),
@@ -1094,6 +1818,10 @@ impl App {
             Span::styled(" [r]un ", theme::status_running()),
             Span::styled(" [x]stop ", Style::default().fg(theme::DANGER)),
             Span::styled(" [d]elete ", theme::label()),
+            Span::styled(" [v]iew logs ", theme::label()),
+            Span::styled(" [t]ype to process ", theme::label()),
+            Span::styled(" [l]og files ", theme::label()),
+            Span::styled(" [w]orkspace ", theme::label()),
             Span::styled(format!(" {} ", cfg_label), theme::label()),
             Span::styled(" [F5]refresh ", theme::label()),
             Span::styled(" [q]uit ", theme::label()),
@@ -1149,18 +1877,90 @@ impl App {
         frame.render_widget(help, area);
     }
 
+    /// Marker for a row whose processes are running: half-filled while a
+    /// health check has yet to pass, red while one is failing.
+    fn running_marker(&self, keys: &[String]) -> (&'static str, Style) {
+        let health: Vec<Health> = keys.iter().filter_map(|k| self.process_manager.health(k)).collect();
+        if health.iter().any(|h| matches!(h, Health::Unhealthy { .. })) {
+            ("\u{25CF}", Style::default().fg(theme::DANGER))
+        } else if health.iter().any(Health::is_starting) {
+            ("\u{25D0}", Style::default().fg(theme::WARNING))
+        } else {
+            ("\u{25CF}", theme::status_running())
+        }
+    }
+
+    /// Badge after a process's name: its restart count while running, or how
+    /// it exited while crashed or waiting to be restarted.
+    fn exit_badge(&self, key: &str) -> Option<Span<'static>> {
+        if self.process_manager.is_running(key) {
+            let restarts = self.process_manager.restarts(key);
+            return (restarts > 0).then(|| {
+                Span::styled(format!(" \u{21BB}{}", restarts), Style::default().fg(theme::WARNING))
+            });
+        }
+
+        let exit = self.process_manager.exit_info(key)?;
+        let how = match (exit.code, exit.signal) {
+            (Some(code), _) => format!("exit {}", code),
+            (None, Some(signal)) => format!("signal {}", signal),
+            (None, None) => "exited".to_string(),
+        };
+        Some(if exit.restarting {
+            Span::styled(format!(" \u{21BB} {}", how), Style::default().fg(theme::WARNING))
+        } else {
+            Span::styled(format!(" \u{2716} {}", how), Style::default().fg(theme::DANGER))
+        })
+    }
+
     fn render_project_list(&mut self, frame: &mut Frame, area: Rect) {
         let items: Vec<ListItem> = self
-            .store
-            .projects
-            .iter()
-            .map(|p| {
+            .list_rows()
+            .into_iter()
+            .map(|row| {
+                let p = &self.store.projects[row.project];
                 let has_path = !p.path.is_empty() && Path::new(&p.path).exists();
-                let is_running = self.process_manager.is_running(&p.name);
+
+                if let Some(svc) = row.service {
+                    let service = &p.services[svc];
+                    let key = p.service_key(&service.name);
+                    let is_running = self.process_manager.is_running(&key);
+
+                    let mut spans = vec![
+                        Span::styled("   \u{2514} ", theme::label()),
+                        if self.is_stopping(&key) {
+                            Span::styled("\u{25CC} ", Style::default().fg(theme::WARNING))
+                        } else if is_running {
+                            let (marker, style) = self.running_marker(std::slice::from_ref(&key));
+                            Span::styled(format!("{} ", marker), style)
+                        } else {
+                            Span::styled("\u{25CB} ", theme::status_stopped())
+                        },
+                        Span::styled(
+                            service.name.clone(),
+                            Style::default().fg(if is_running { theme::FG } else { theme::FG_DIM }),
+                        ),
+                    ];
+                    if let Some(port) = self.process_manager.get_port(&key) {
+                        spans.push(Span::styled(
+                            format!(" :{}", port),
+                            Style::default().fg(theme::STATUS_PORT),
+                        ));
+                    }
+                    spans.extend(self.exit_badge(&key));
+                    return ListItem::new(Line::from(spans));
+                }
+
+                // A project counts as running while any of its processes is
+                let keys = p.process_keys();
+                let is_running = keys.iter().any(|k| self.process_manager.is_running(k));
                 let port = self.process_manager.get_port(&p.name);
 
-                let indicator = if is_running {
-                    Span::styled(" \u{25CF} ", theme::status_running())
+                let indicator = if keys.iter().any(|k| self.is_stopping(k)) {
+                    Span::styled(" \u{25CC} ", Style::default().fg(theme::WARNING))
+                } else if is_running {
+                    let (marker, style) = self.running_marker(&keys);
+                    Span::styled(format!(" {} ", marker), style)
                 } else if has_path {
                     Span::styled(" \u{25CB} ", theme::status_stopped())
                 } else {
@@ -1181,6 +1981,7 @@ impl App {
                         Style::default().fg(theme::STATUS_PORT),
                     ));
                 }
+                spans.extend(self.exit_badge(&p.name));
 
                 ListItem::new(Line::from(spans))
             })
@@ -1227,11 +2028,18 @@ impl App {
                     lines.push(Line::from(vec![
                         Span::styled("  Type      ", theme::label()),
                         Span::raw(if let Some(pm) = det.package_manager {
-                            format!("{:?} ({})", det.project_type, pm.as_str())
+                            format!("{} ({})", det.project_type.label(), pm.as_str())
                         } else {
-                            format!("{:?}", det.project_type)
+                            det.project_type.label().to_string()
                         }),
                     ]));
+                    let runnable = det.packages.iter().filter(|p| p.run_command.is_some()).count();
+                    if runnable > 0 {
+                        lines.push(Line::from(vec![
+                            Span::styled("  Workspace ", theme::label()),
+                            Span::raw(format!("{} runnable packages ([w] to add)", runnable)),
+                        ]));
+                    }
                 }
 
                 lines.push(Line::from(""));
@@ -1286,28 +2094,98 @@ impl App {
                     Span::styled("  \u{2500}\u{2500} Runtime \u{2500}\u{2500}", Style::default().fg(theme::ACCENT)),
                 ]));
 
-                let is_running = self.process_manager.is_running(&project.name);
-                let port = self.process_manager.get_port(&project.name);
+                let key = self.selected_key().unwrap_or_else(|| project.name.clone());
+                let is_running = self.process_manager.is_running(&key);
+                let port = self.process_manager.get_port(&key);
+                let service = self.selected_service();
 
-                lines.push(Line::from(vec![
-                    Span::styled("  Status    ", theme::label()),
-                    if is_running {
-                        Span::styled("\u{25CF} Running", theme::status_running())
-                    } else {
-                        Span::styled("\u{25CB} Stopped", theme::status_stopped())
-                    },
-                ]));
+                if let Some(service) = service {
+                    lines.push(Line::from(vec![
+                        Span::styled("  Service   ", theme::label()),
+                        Span::raw(&service.name),
+                    ]));
+                    if let Some(ref cwd) = service.cwd {
+                        lines.push(Line::from(vec![
+                            Span::styled("  Cwd       ", theme::label()),
+                            Span::raw(cwd),
+                        ]));
+                    }
+                }
+
+                let restarts = self.process_manager.restarts(&key);
+                let status = match self.process_manager.exit_info(&key) {
+                    _ if self.is_stopping(&key) => {
+                        Span::styled("\u{25CC} Stopping...", Style::default().fg(theme::WARNING))
+                    }
+                    _ if is_running => {
+                        let (marker, style) = self.running_marker(std::slice::from_ref(&key));
+                        let state = match self.process_manager.health(&key) {
+                            None => "Running".to_string(),
+                            Some(Health::Starting { last_error: None }) => "Starting".to_string(),
+                            Some(Health::Starting { last_error: Some(error) }) => {
+                                format!("Starting (last check: {})", error)
+                            }
+                            Some(Health::Ready) => "Ready".to_string(),
+                            Some(Health::Unhealthy { reason }) => format!("Unhealthy ({})", reason),
+                        };
+                        match restarts {
+                            0 => Span::styled(format!("{} {}", marker, state), style),
+                            n => Span::styled(format!("{} {} (restarted {}\u{00D7})", marker, state, n), style),
+                        }
+                    }
+                    Some(exit) => {
+                        let how = process::describe_exit(exit.code, exit.signal);
+                        if exit.restarting {
+                            Span::styled(
+                                format!("\u{21BB} Restarting ({}, attempt {})", how, exit.restarts + 1),
+                                Style::default().fg(theme::WARNING),
+                            )
+                        } else if exit.restarts > 0 {
+                            Span::styled(
+                                format!("\u{2716} Crashed ({}, after {} restarts)", how, exit.restarts),
+                                Style::default().fg(theme::DANGER),
+                            )
+                        } else {
+                            Span::styled(format!("\u{2716} Crashed ({})", how), Style::default().fg(theme::DANGER))
+                        }
+                    }
+                    None => Span::styled("\u{25CB} Stopped", theme::status_stopped()),
+                };
+                lines.push(Line::from(vec![Span::styled("  Status    ", theme::label()), status]));
 
                 if let Some(port) = port {
-                    lines.push(Line::from(vec![
+                    let mut spans = vec![
                         Span::styled("  Port      ", theme::label()),
                         Span::styled(port.to_string(), Style::default().fg(theme::STATUS_PORT)),
-                    ]));
+                    ];
+                    if is_running && !self.process_manager.port_confirmed(&key) {
+                        spans.push(Span::styled(" (not seen listening yet)", theme::status_stopped()));
+                    }
+                    lines.push(Line::from(spans));
                 }
 
-                let cmd = project.run_command.as_ref().or_else(|| {
-                    self.selected_detection.as_ref().and_then(|d| d.run_command.as_ref())
-                });
+                if let (None, Some(reserved)) = (service, project.port) {
+                    let mut spans = vec![
+                        Span::styled("  Reserved  ", theme::label()),
+                        Span::raw(reserved.to_string()),
+                    ];
+                    if project.pin_port {
+                        spans.push(Span::raw(" (pinned)"));
+                    }
+                    let conflicts = self.store.port_conflicts(&project.name);
+                    if !conflicts.is_empty() {
+                        spans.push(Span::styled(
+                            format!("  also reserved by {}", conflicts.join(", ")),
+                            Style::default().fg(theme::WARNING),
+                        ));
+                    }
+                    lines.push(Line::from(spans));
+                }
+
+                let cmd = match service {
+                    Some(service) => Some(service.command.clone()),
+                    None => launch::dev_server_command(project, self.selected_detection.as_ref()),
+                };
                 if let Some(cmd) = cmd {
                     lines.push(Line::from(vec![
                         Span::styled("  Command   ", theme::label()),
@@ -1315,7 +2193,28 @@ impl App {
                     ]));
                 }
 
-                if let Some(ref override_cmd) = project.run_command {
+                if project.shell {
+                    lines.push(Line::from(vec![
+                        Span::styled("  Shell     ", theme::label()),
+                        Span::raw("login shell ($SHELL -lc)"),
+                    ]));
+                }
+
+                if project.pty {
+                    lines.push(Line::from(vec![
+                        Span::styled("  Terminal  ", theme::label()),
+                        Span::raw("pseudo-terminal"),
+                    ]));
+                }
+
+                if project.force_color {
+                    lines.push(Line::from(vec![
+                        Span::styled("  Color     ", theme::label()),
+                        Span::raw("forced (FORCE_COLOR, CLICOLOR_FORCE)"),
+                    ]));
+                }
+
+                if let (None, Some(override_cmd)) = (service, &project.run_command) {
                     // Only show override if it's different from the detected command
                     let detected_cmd = self.selected_detection.as_ref().and_then(|d| d.run_command.as_ref());
                     if detected_cmd.is_some() && detected_cmd != Some(override_cmd) {
@@ -1325,6 +2224,39 @@ impl App {
                         ]));
                     }
                 }
+
+                // Environment section
+                if !project.env_files.is_empty() || !self.selected_env.is_empty() {
+                    lines.push(Line::from(""));
+                    lines.push(Line::from(vec![
+                        Span::styled("  \u{2500}\u{2500} Environment \u{2500}\u{2500}", Style::default().fg(theme::ACCENT)),
+                    ]));
+
+                    if !project.env_files.is_empty() {
+                        let mut spans = vec![Span::styled("  Files     ", theme::label())];
+                        for (i, file) in project.env_files.iter().enumerate() {
+                            if i > 0 {
+                                spans.push(Span::raw(", "));
+                            }
+                            if Path::new(&project.path).join(file).exists() {
+                                spans.push(Span::raw(file.as_str()));
+                            } else {
+                                spans.push(Span::styled(
+                                    format!("{} (missing)", file),
+                                    Style::default().fg(theme::WARNING),
+                                ));
+                            }
+                        }
+                        lines.push(Line::from(spans));
+                    }
+
+                    for (key, value) in &self.selected_env {
+                        lines.push(Line::from(vec![
+                            Span::styled(format!("  {}=", key), theme::label()),
+                            Span::raw(env::mask(key, value)),
+                        ]));
+                    }
+                }
             } else {
                 lines.push(Line::from(""));
                 lines.push(Line::from(Span::styled(
@@ -1354,3 +2286,108 @@ impl App {
         frame.render_widget(para, area);
     }
 }
+
+/// What a terminal would send for a key, in key-by-key input mode.
+fn key_input(key: KeyCode) -> Option<String> {
+    let seq = match key {
+        KeyCode::Char(c) => return Some(c.to_string()),
+        KeyCode::Enter => "\n",
+        KeyCode::Backspace => "\x7f",
+        KeyCode::Up => "\x1b[A",
+        KeyCode::Down => "\x1b[B",
+        KeyCode::Right => "\x1b[C",
+        KeyCode::Left => "\x1b[D",
+        KeyCode::Home => "\x1b[H",
+        KeyCode::End => "\x1b[F",
+        KeyCode::Delete => "\x1b[3~",
+        _ => return None,
+    };
+    Some(seq.to_string())
+}
+
+/// The logs pane's bottom line while typing to a process.
+fn stdin_prompt(input: &StdinInput) -> Line<'static> {
+    let mut spans = match input.line {
+        Some(ref line) => vec![
+            Span::styled(format!("{} > ", input.key), theme::accent_title()),
+            Span::raw(line.clone()),
+            Span::styled("\u{2588}", theme::accent_title()),
+            Span::styled("  [Enter] send  [Tab] keys  [Esc] done", theme::label()),
+        ],
+        None => vec![
+            Span::styled(format!("{} \u{2190} keys ", input.key), theme::accent_title()),
+            Span::styled(" sent as you type  [Tab] line  [Esc] done", theme::label()),
+        ],
+    };
+    if let Some(ref error) = input.error {
+        spans.push(Span::styled(format!("  {}", error), Style::default().fg(theme::DANGER)));
+    }
+    Line::from(spans)
+}
+
+/// Title of the logs pane while a project's pipeline is shown.
+fn pipeline_title(name: &str, pipeline: &Pipeline) -> String {
+    let label = pipeline.kind.label();
+    match (pipeline.failure(), pipeline.current(), pipeline.kind) {
+        (Some(step), _, Kind::Run) => {
+            format!(" {} {}: {} failed ([r] retry, Esc dismiss) ", label, name, step.name)
+        }
+        (Some(step), _, Kind::Stop) => format!(" {} {}: {} failed (Esc dismiss) ", label, name, step.name),
+        (None, Some(step), _) => format!(" {} {}: {}... ", label, name, step.name),
+        (None, None, _) => format!(" {} {} ", label, name),
+    }
+}
+
+/// A run pipeline's steps with their state, then the tail of its output.
+fn pipeline_lines(pipeline: &Pipeline, height: usize) -> Vec<Line<'static>> {
+    const SPINNER: [char; 4] = ['\u{25D0}', '\u{25D3}', '\u{25D1}', '\u{25D2}'];
+
+    let mut lines: Vec<Line> = pipeline
+        .steps
+        .iter()
+        .map(|step| {
+            let (icon, style) = match step.state {
+                StepState::Pending => ('\u{25CB}', theme::label()),
+                StepState::Running => {
+                    let frame = step.elapsed().unwrap_or_default().as_millis() / 250;
+                    (SPINNER[frame as usize % SPINNER.len()], Style::default().fg(theme::ACCENT))
+                }
+                StepState::Done => ('\u{2714}', theme::status_running()),
+                StepState::Failed(_) if step.required => ('\u{2716}', Style::default().fg(theme::DANGER)),
+                StepState::Failed(_) => ('\u{26A0}', Style::default().fg(theme::WARNING)),
+                StepState::Skipped(_) => ('\u{2013}', theme::label()),
+            };
+            let mut spans = vec![
+                Span::styled(format!(" {} ", icon), style),
+                Span::raw(format!("{:<24} ", step.name)),
+            ];
+            if let Some(elapsed) = step.elapsed() {
+                spans.push(Span::styled(format!("{:.1}s", elapsed.as_secs_f64()), theme::label()));
+            }
+            if let StepState::Failed(ref note) | StepState::Skipped(ref note) = step.state {
+                spans.push(Span::styled(format!("  {}", note), style));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    if !pipeline.output.is_empty() {
+        lines.push(Line::from(Span::styled(" \u{2500}\u{2500} output \u{2500}\u{2500}", theme::label())));
+        let room = height.saturating_sub(lines.len());
+        let start = pipeline.output.len().saturating_sub(room);
+        lines.extend(pipeline.output[start..].iter().map(|l| ansi::to_line(l)));
+    }
+    lines
+}
+
+/// A step that failed without stopping the run, above the process's output.
+fn warning_line(step: &Step) -> Line<'static> {
+    let error = match step.state {
+        StepState::Failed(ref e) => e.as_str(),
+        _ => "",
+    };
+    Line::from(Span::styled(
+        format!("\u{26A0} {} failed: {} (Esc to dismiss)", step.name, error),
+        Style::default().fg(theme::WARNING),
+    ))
+}
@@ -0,0 +1,823 @@
+use anyhow::{bail, Result};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::app::App;
+use crate::detect;
+use crate::env;
+use crate::git_status;
+use crate::launch::{self, Target};
+use crate::logs;
+use crate::ports;
+use crate::process::{self, ProcessManager};
+use crate::report::ProjectReport;
+use crate::scanner;
+use crate::signals::{install_stop_handler, stop_requested};
+use crate::store::{ProjectEntry, ProjectStore, ServiceEntry};
+
+const USAGE: &str = "\
+Usage: claude-manager [COMMAND]
+
+Without a command, opens the interactive dashboard.
+
+Commands:
+  list              List projects and whether their dev server is running
+  add <path>        Add a local project directory
+  remove <name>     Remove a project (files on disk are left alone)
+  run <name>        Run a project's dev server and services (in the foreground,
+                    or under the supervisor when one is running). Use
+                    <project>/<service> to run a single service
+  stop <name>       Stop a dev server or service started with `run`
+  logs <name>       Print a process's output (-f to follow); without the
+                    supervisor, prints its latest log file
+  status [name]     Show project details (all projects if no name is given)
+                    --json prints the same data as JSON
+  ports             List open dev server ports and the processes holding them
+  daemon            Run the supervisor in the foreground. Dev servers started
+                    while it runs survive closing the dashboard
+  daemon start      Start the supervisor in the background
+  daemon stop       Stop the supervisor and every dev server it owns
+  daemon status     Show whether the supervisor is running
+  help              Show this message";
+
+/// Dispatch a headless subcommand. `args` excludes the program name.
+pub fn run(args: &[String]) -> Result<()> {
+    let command = args[0].as_str();
+    let args = &args[1..];
+
+    match command {
+        "list" | "ls" => parse_args(command, args, &[], &[]).and_then(|_| list()),
+        "add" => add(parse_args(command, args, &["path"], &[])?.required(0)),
+        "remove" | "rm" => remove(parse_args(command, args, &["name"], &[])?.required(0)),
+        "run" => run_project(parse_args(command, args, &["name"], &[])?.required(0)),
+        "stop" => stop(parse_args(command, args, &["name"], &[])?.required(0)),
+        "status" => {
+            let parsed = parse_args(command, args, &["[name]"], &["--json"])?;
+            match parsed.has("--json") {
+                true => status_json(parsed.optional(0)),
+                false => status(parsed.optional(0)),
+            }
+        }
+        "logs" => {
+            let parsed = parse_args(command, args, &["name"], &["-f", "--follow"])?;
+            logs(parsed.required(0), parsed.has("-f") || parsed.has("--follow"))
+        }
+        "ports" => parse_args(command, args, &[], &[]).and_then(|_| list_ports()),
+        "daemon" => daemon(parse_args(command, args, &["[action]"], &[])?.optional(0)),
+        "help" | "-h" | "--help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => bail!("Unknown command '{}'\n\n{}", other, USAGE),
+    }
+}
+
+/// A subcommand's arguments, checked against what it accepts.
+struct ParsedArgs<'a> {
+    positional: Vec<&'a str>,
+    flags: Vec<&'a str>,
+}
+
+impl<'a> ParsedArgs<'a> {
+    /// A positional argument that `parse_args` made sure is there.
+    fn required(&self, index: usize) -> &'a str {
+        self.positional[index]
+    }
+
+    fn optional(&self, index: usize) -> Option<&'a str> {
+        self.positional.get(index).copied()
+    }
+
+    fn has(&self, flag: &str) -> bool {
+        self.flags.contains(&flag)
+    }
+}
+
+/// Split `args` into positionals and flags. `positional` names each accepted
+/// argument, optional ones in brackets; an unknown flag, a missing argument
+/// or one too many is a usage error.
+fn parse_args<'a>(
+    command: &str,
+    args: &'a [String],
+    positional: &[&str],
+    flags: &[&str],
+) -> Result<ParsedArgs<'a>> {
+    let mut parsed = ParsedArgs {
+        positional: Vec::new(),
+        flags: Vec::new(),
+    };
+    for arg in args {
+        let arg = arg.as_str();
+        if arg.starts_with('-') && arg.len() > 1 {
+            if !flags.contains(&arg) {
+                bail!("Unknown option '{}' for '{}'\n\n{}", arg, command, USAGE);
+            }
+            parsed.flags.push(arg);
+        } else if parsed.positional.len() < positional.len() {
+            parsed.positional.push(arg);
+        } else {
+            bail!("Unexpected argument '{}' for '{}'\n\n{}", arg, command, USAGE);
+        }
+    }
+    if let Some(name) = positional.get(parsed.positional.len()) {
+        if !name.starts_with('[') {
+            bail!("Missing <{}> argument\n\n{}", name, USAGE);
+        }
+    }
+    Ok(parsed)
+}
+
+fn find_project<'a>(store: &'a ProjectStore, name: &str) -> Result<&'a ProjectEntry> {
+    match store.get(name) {
+        Some(p) => Ok(p),
+        None => bail!("No project named '{}'", name),
+    }
+}
+
+/// Resolve `project` or `project/service`.
+fn find_target<'a>(
+    store: &'a ProjectStore,
+    target: &str,
+) -> Result<(&'a ProjectEntry, Option<&'a ServiceEntry>)> {
+    let (project_name, service_name) = match target.split_once('/') {
+        Some((p, s)) => (p, Some(s)),
+        None => (target, None),
+    };
+    let project = find_project(store, project_name)?;
+    let service = match service_name {
+        Some(s) => match project.service(s) {
+            Some(service) => Some(service),
+            None => bail!("Project '{}' has no service named '{}'", project.name, s),
+        },
+        None => None,
+    };
+    Ok((project, service))
+}
+
+fn list() -> Result<()> {
+    let store = ProjectStore::load()?;
+    if store.projects.is_empty() {
+        println!("No projects. Add one with `claude-manager add <path>`.");
+        return Ok(());
+    }
+
+    let pm = ProcessManager::attach();
+    let state = |key: &str| match find_running(&pm, key) {
+        Some(run) => match run.port {
+            Some(port) => format!("running :{}", port),
+            None => "running".to_string(),
+        },
+        None => "stopped".to_string(),
+    };
+
+    let width = store
+        .projects
+        .iter()
+        .flat_map(|p| {
+            std::iter::once(p.name.len()).chain(p.services.iter().map(|s| s.name.len() + 4))
+        })
+        .max()
+        .unwrap_or(0);
+    for project in &store.projects {
+        let path = if project.path.is_empty() {
+            "(not cloned)"
+        } else {
+            project.path.as_str()
+        };
+        println!(
+            "{:<width$}  {:<12}  {}",
+            project.name,
+            state(&project.name),
+            path,
+            width = width
+        );
+        for service in &project.services {
+            println!(
+                "{:<width$}  {}",
+                format!("  \u{2514} {}", service.name),
+                state(&project.service_key(&service.name)),
+                width = width
+            );
+        }
+    }
+    Ok(())
+}
+
+fn add(path_str: &str) -> Result<()> {
+    let path = Path::new(path_str);
+    if !path.is_dir() {
+        bail!("'{}' is not a directory", path_str);
+    }
+
+    let path = App::clean_path(path);
+    let name = match path.file_name().and_then(|n| n.to_str()) {
+        Some(n) => n.to_string(),
+        None => bail!("Cannot derive a project name from '{}'", path.display()),
+    };
+
+    let mut store = ProjectStore::load()?;
+    if store.get(&name).is_some() {
+        bail!("A project named '{}' already exists", name);
+    }
+
+    store.add(ProjectEntry {
+        name: name.clone(),
+        repo_url: scanner::get_git_remote(&path),
+        path: path.to_string_lossy().to_string(),
+        run_command: None,
+        ..Default::default()
+    });
+    store.save()?;
+    println!("Added {} ({})", name, path.display());
+    Ok(())
+}
+
+fn remove(name: &str) -> Result<()> {
+    let mut store = ProjectStore::load()?;
+    let name = find_project(&store, name)?.name.clone();
+    store.remove(&name);
+    store.save()?;
+    println!("Removed {}", name);
+    Ok(())
+}
+
+fn run_project(target: &str) -> Result<()> {
+    let store = ProjectStore::load()?;
+    let (project, service) = find_target(&store, target)?;
+
+    let path = PathBuf::from(&project.path);
+    if project.path.is_empty() || !path.exists() {
+        bail!("Project '{}' has no local path", project.name);
+    }
+
+    let run_key = match service {
+        Some(s) => project.service_key(&s.name),
+        None => project.name.clone(),
+    };
+    let mut pm = ProcessManager::attach();
+    pm.set_logging(&store.logs);
+    // Processes in a pseudo-terminal get the size of ours
+    let sync_size = |pm: &mut ProcessManager| {
+        if let (false, Ok((cols, rows))) = (pm.is_attached(), crossterm::terminal::size()) {
+            pm.resize_ptys(cols, rows);
+        }
+    };
+    sync_size(&mut pm);
+    if let Some(run) = find_running(&pm, &run_key) {
+        bail!("'{}' is already running (pid {})", run_key, run.pid);
+    }
+
+    let targets = match service {
+        Some(s) => vec![launch::service(project, s)],
+        None => {
+            let detection = detect::detect(&path).ok();
+            if let Some(holder) = launch::pinned_port_holder(project, detection.as_ref()) {
+                bail!(
+                    "Port {} is pinned for '{}' but held by {}; stop it or set pin_port = false",
+                    holder.port,
+                    project.name,
+                    holder.describe()
+                );
+            }
+            launch::all(project, detection.as_ref(), &store)
+        }
+    };
+    if targets.is_empty() {
+        bail!("No run command for '{}'; set one in the dashboard with 'e'", project.name);
+    }
+    // Services already running under the supervisor are left alone
+    let targets: Vec<Target> = targets
+        .into_iter()
+        .filter(|t| !pm.is_running(&t.key))
+        .collect();
+
+    for (i, target) in targets.iter().enumerate() {
+        if let Err(e) = pm.launch(&target.key, &target.spec) {
+            if !pm.is_attached() {
+                for started in &targets[..i] {
+                    let _ = pm.stop(&started.key);
+                }
+            }
+            return Err(e.context(format!("Failed to start {}", target.key)));
+        }
+    }
+
+    // Keep the dev server's port for next time
+    let dev_port = targets.iter().find(|t| t.key == project.name).and_then(|t| t.spec.port);
+    if let Some(port) = dev_port {
+        let mut store = ProjectStore::load()?;
+        if store.remember_port(&project.name, port) {
+            store.save()?;
+        }
+    }
+
+    if pm.is_attached() {
+        for target in &targets {
+            match target.spec.port {
+                Some(p) => println!("Started {} under the supervisor on port {}", target.key, p),
+                None => println!("Started {} under the supervisor", target.key),
+            }
+        }
+        return Ok(());
+    }
+
+    let run = RunFile {
+        pid: std::process::id(),
+        port: targets.first().and_then(|t| t.spec.port),
+    };
+    run.write(&run_key)?;
+
+    for target in &targets {
+        match target.spec.port {
+            Some(p) => eprintln!("Started {} on port {}: {}", target.key, p, target.spec.command),
+            None => eprintln!("Started {}: {}", target.key, target.spec.command),
+        }
+    }
+
+    install_stop_handler();
+
+    // With several processes, prefix each line with the one it came from
+    let label = |target: &Target| match target.key.split_once('/') {
+        Some((_, service)) if targets.len() > 1 => format!("[{}] ", service),
+        _ if targets.len() > 1 => format!("[{}] ", target.key),
+        _ => String::new(),
+    };
+    let print_new = |pm: &ProcessManager, target: &Target, seen: &mut usize| {
+        let (lines, total) = pm.get_output_since(&target.key, *seen);
+        *seen = total;
+        let prefix = label(target);
+        let mut out = io::stdout().lock();
+        for line in lines {
+            let _ = writeln!(out, "{}{}", prefix, line);
+        }
+        let _ = out.flush();
+    };
+
+    let mut seen = vec![0; targets.len()];
+    let mut alive = vec![true; targets.len()];
+    loop {
+        for (i, target) in targets.iter().enumerate() {
+            if alive[i] {
+                print_new(&pm, target, &mut seen[i]);
+            }
+        }
+
+        pm.reap_dead();
+        sync_size(&mut pm);
+        for (i, target) in targets.iter().enumerate() {
+            // A crashed process waiting for its restart is still ours to follow
+            if alive[i] && !pm.is_running(&target.key) && !pm.is_restarting(&target.key) {
+                // Let the capture threads drain what the process wrote before exiting
+                std::thread::sleep(Duration::from_millis(100));
+                print_new(&pm, target, &mut seen[i]);
+                eprintln!("{} exited", target.key);
+                alive[i] = false;
+            }
+        }
+        if !alive.contains(&true) {
+            break;
+        }
+        if stop_requested() {
+            let mut keys = Vec::new();
+            for target in &targets {
+                if pm.is_running(&target.key) {
+                    eprintln!("Stopping {}...", target.key);
+                    keys.push(target.key.clone());
+                } else if pm.is_restarting(&target.key) {
+                    keys.push(target.key.clone());
+                }
+            }
+            pm.stop_many(&keys)?;
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    RunFile::remove(&run_key);
+    Ok(())
+}
+
+fn stop(target: &str) -> Result<()> {
+    let store = ProjectStore::load()?;
+    let (project, service) = find_target(&store, target)?;
+
+    let (run_key, keys) = match service {
+        Some(s) => {
+            let key = project.service_key(&s.name);
+            (key.clone(), vec![key])
+        }
+        None => (project.name.clone(), project.process_keys()),
+    };
+
+    let mut pm = ProcessManager::attach();
+    // Crashed processes are included, so a pending restart is cancelled too
+    let supervised: Vec<String> = keys
+        .into_iter()
+        .filter(|k| pm.is_running(k) || pm.exit_info(k).is_some())
+        .collect();
+    if !supervised.is_empty() {
+        pm.stop_many(&supervised)?;
+        for key in supervised {
+            println!("Stopped {}", key);
+        }
+        return Ok(());
+    }
+
+    let run = match RunFile::read(&run_key) {
+        Some(r) => r,
+        None => bail!("'{}' is not running", run_key),
+    };
+
+    // Ask the foreground `run` process to shut down; it stops the dev server itself.
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(run.pid as i32, libc::SIGTERM);
+    }
+    #[cfg(windows)]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/PID", &run.pid.to_string(), "/T", "/F"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status();
+        RunFile::remove(&run_key);
+    }
+
+    for _ in 0..50 {
+        if RunFile::read(&run_key).is_none() {
+            println!("Stopped {}", run_key);
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    bail!("Timed out waiting for '{}' to stop (pid {})", run_key, run.pid)
+}
+
+fn status(name: Option<&str>) -> Result<()> {
+    let store = ProjectStore::load()?;
+    let projects: Vec<&ProjectEntry> = match name {
+        Some(n) => vec![find_project(&store, n)?],
+        None => store.projects.iter().collect(),
+    };
+
+    let pm = ProcessManager::attach();
+    for (i, project) in projects.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print_status(&pm, project);
+    }
+    Ok(())
+}
+
+/// Print a single report object for a named project, or an array for all of them.
+fn status_json(name: Option<&str>) -> Result<()> {
+    let store = ProjectStore::load()?;
+    let pm = ProcessManager::attach();
+    let report = |project: &ProjectEntry| {
+        let running = project
+            .process_keys()
+            .iter()
+            .any(|k| find_running(&pm, k).is_some());
+        let port = find_running(&pm, &project.name).and_then(|r| r.port);
+        ProjectReport::collect(project, running, port)
+    };
+
+    let json = match name {
+        Some(n) => serde_json::to_string_pretty(&report(find_project(&store, n)?))?,
+        None => {
+            let reports: Vec<ProjectReport> = store.projects.iter().map(report).collect();
+            serde_json::to_string_pretty(&reports)?
+        }
+    };
+    println!("{}", json);
+    Ok(())
+}
+
+fn print_status(pm: &ProcessManager, project: &ProjectEntry) {
+    println!("{}", project.name);
+    if let Some(ref url) = project.repo_url {
+        println!("  Repo      {}", url);
+    }
+
+    let path = Path::new(&project.path);
+    if project.path.is_empty() || !path.exists() {
+        println!("  Path      not set");
+        return;
+    }
+    println!("  Path      {}", project.path);
+
+    let detection = detect::detect(path).ok();
+    if let Some(ref det) = detection {
+        match det.package_manager {
+            Some(pm) => println!("  Type      {} ({})", det.project_type.label(), pm.as_str()),
+            None => println!("  Type      {}", det.project_type.label()),
+        }
+    }
+
+    if let Ok(git) = git_status::get_status(path) {
+        println!("  Branch    {}", git.branch);
+        if git.staged == 0 && git.modified == 0 && git.untracked == 0 {
+            println!("  Git       clean");
+        } else {
+            println!(
+                "  Git       {} staged, {} modified, {} untracked",
+                git.staged, git.modified, git.untracked
+            );
+        }
+        println!("  Ahead     {}    Behind  {}", git.ahead, git.behind);
+    }
+
+    match find_running(pm, &project.name) {
+        Some(run) if pm.is_running(&project.name) => {
+            println!("  Status    running under the supervisor (pid {})", run.pid);
+            if let Some(port) = run.port {
+                println!("  Port      {}", port);
+            }
+        }
+        Some(run) => {
+            println!("  Status    running (pid {})", run.pid);
+            if let Some(port) = run.port {
+                println!("  Port      {}", port);
+            }
+        }
+        None => println!("  Status    {}", exit_status(pm, &project.name)),
+    }
+
+    if let Some(cmd) = launch::dev_server_command(project, detection.as_ref()) {
+        println!("  Command   {}", cmd);
+    }
+
+    for service in &project.services {
+        let state = match find_running(pm, &project.service_key(&service.name)) {
+            Some(run) => match run.port {
+                Some(port) => format!("running (pid {}, port {})", run.pid, port),
+                None => format!("running (pid {})", run.pid),
+            },
+            None => exit_status(pm, &project.service_key(&service.name)),
+        };
+        println!("  Service   {}: {} [{}]", service.name, state, service.command);
+    }
+
+    for (key, value) in project.resolve_env() {
+        println!("  Env       {}={}", key, env::mask(&key, &value));
+    }
+}
+
+/// Status of a process that isn't running: how it crashed or how its last run
+/// ended, if the supervisor saw it, otherwise just "stopped".
+fn exit_status(pm: &ProcessManager, key: &str) -> String {
+    let exit = match (pm.exit_info(key), pm.last_run(key)) {
+        (Some(e), _) => e,
+        (None, Some(run)) => return format!("stopped; last run {}", run.summary()),
+        (None, None) => return "stopped".to_string(),
+    };
+    let how = process::describe_exit(exit.code, exit.signal);
+    if exit.restarting {
+        format!("restarting ({}, attempt {})", how, exit.restarts + 1)
+    } else {
+        format!("crashed ({}, {} restarts)", how, exit.restarts)
+    }
+}
+
+fn list_ports() -> Result<()> {
+    let store = ProjectStore::load()?;
+    let open = ports::scan_ports(&store.ports);
+    if open.is_empty() {
+        println!("No active dev server ports.");
+        return Ok(());
+    }
+    println!("{:<7}{:<9}PROCESS", "PORT", "PID");
+    for info in open {
+        let pid = info.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string());
+        let process = info.process_name.as_deref().unwrap_or("-");
+        println!("{:<7}{:<9}{}", info.port, pid, process);
+    }
+    Ok(())
+}
+
+fn logs(name: &str, follow: bool) -> Result<()> {
+    let store = ProjectStore::load()?;
+    let (project, service) = find_target(&store, name)?;
+    let key = match service {
+        Some(s) => project.service_key(&s.name),
+        None => project.name.clone(),
+    };
+
+    let mut pm = ProcessManager::attach();
+    if !pm.is_attached() {
+        return print_log_file(&key, follow);
+    }
+    if !pm.is_running(&key) && pm.exit_info(&key).is_none() {
+        // Gone: show how its last run ended instead
+        let run = match pm.last_run(&key) {
+            Some(run) => run,
+            None => bail!("'{}' is not running under the supervisor", key),
+        };
+        for line in &run.tail {
+            println!("{}", line);
+        }
+        eprintln!("-- {} {} --", key, run.summary());
+        return Ok(());
+    }
+
+    let mut seen = 0;
+    loop {
+        let (lines, total) = pm.get_output_since(&key, seen);
+        seen = total;
+        for line in lines {
+            println!("{}", line);
+        }
+        if !follow {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(250));
+        pm.reap_dead();
+        if !pm.is_running(&key) && !pm.is_restarting(&key) {
+            // Pick up whatever it wrote on the way out
+            let (lines, _) = pm.get_output_since(&key, seen);
+            for line in lines {
+                println!("{}", line);
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Print the newest log file of a process, optionally following it as it grows.
+fn print_log_file(key: &str, follow: bool) -> Result<()> {
+    let entry = match logs::list(key).into_iter().next() {
+        Some(e) => e,
+        None => bail!("No logs for '{}'", key),
+    };
+    eprintln!("-- {} --", entry.path.display());
+
+    let mut file = fs::File::open(&entry.path)?;
+    let mut chunk = Vec::new();
+    loop {
+        chunk.clear();
+        file.read_to_end(&mut chunk)?;
+        if !chunk.is_empty() {
+            let mut out = io::stdout().lock();
+            out.write_all(&chunk)?;
+            out.flush()?;
+        }
+        if !follow {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+}
+
+fn daemon(action: Option<&str>) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use crate::supervisor::{self, Request, SupervisorClient};
+
+        match action {
+            None => supervisor::serve(),
+            Some("start") => {
+                supervisor::spawn_detached()?;
+                println!("Supervisor started");
+                Ok(())
+            }
+            Some("stop") => match SupervisorClient::connect() {
+                Some(client) => {
+                    client.send(&Request::Shutdown, supervisor::REPLY_TIMEOUT)?;
+                    println!("Supervisor stopped");
+                    Ok(())
+                }
+                None => bail!("The supervisor is not running"),
+            },
+            Some("status") => {
+                match SupervisorClient::connect() {
+                    Some(client) => {
+                        println!("Supervisor running, {} process(es)", client.processes().len());
+                        for p in client.processes() {
+                            match p.port {
+                                Some(port) => println!("  {} (pid {}, port {})", p.name, p.pid, port),
+                                None => println!("  {} (pid {})", p.name, p.pid),
+                            }
+                        }
+                    }
+                    None => println!("Supervisor not running"),
+                }
+                Ok(())
+            }
+            Some(other) => bail!("Unknown daemon action '{}'\n\n{}", other, USAGE),
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = action;
+        bail!("The supervisor is only available on Unix")
+    }
+}
+
+/// Running state of a project, whether it lives under the supervisor or in a
+/// foreground `run` in another shell.
+fn find_running(pm: &ProcessManager, name: &str) -> Option<RunFile> {
+    if pm.is_running(name) {
+        return Some(RunFile {
+            pid: pm.get_pid(name)?,
+            port: pm.get_port(name),
+        });
+    }
+    RunFile::read(name)
+}
+
+/// Marker written by a foreground `run` so other invocations can find and stop it.
+/// Lives at `~/.claude-manager/run/<project>.pid` as "<pid> [port]".
+struct RunFile {
+    pid: u32,
+    port: Option<u16>,
+}
+
+impl RunFile {
+    fn path(project_name: &str) -> Option<PathBuf> {
+        dirs::home_dir().map(|home| {
+            home.join(".claude-manager")
+                .join("run")
+                .join(format!("{}.pid", project_name))
+        })
+    }
+
+    /// Read the marker for a project, ignoring (and cleaning up) stale files
+    /// whose process is gone.
+    fn read(project_name: &str) -> Option<Self> {
+        let path = Self::path(project_name)?;
+        let content = fs::read_to_string(&path).ok()?;
+        let mut parts = content.split_whitespace();
+        let pid: u32 = parts.next()?.parse().ok()?;
+        let port = parts.next().and_then(|p| p.parse().ok());
+
+        if !is_pid_alive(pid) {
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+        Some(Self { pid, port })
+    }
+
+    fn write(&self, project_name: &str) -> Result<()> {
+        let path = match Self::path(project_name) {
+            Some(p) => p,
+            None => bail!("Cannot find home directory"),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = match self.port {
+            Some(port) => format!("{} {}\n", self.pid, port),
+            None => format!("{}\n", self.pid),
+        };
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    fn remove(project_name: &str) {
+        if let Some(path) = Self::path(project_name) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn is_pid_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        unsafe { libc::kill(pid as i32, 0) == 0 }
+    }
+
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        let status = args(&["shop", "--json"]);
+        let parsed = parse_args("status", &status, &["[name]"], &["--json"]).unwrap();
+        assert_eq!(parsed.optional(0), Some("shop"));
+        assert!(parsed.has("--json"));
+        assert!(parse_args("status", &[], &["[name]"], &["--json"]).is_ok());
+
+        // Flags belong to their subcommand
+        assert!(parse_args("run", &args(&["shop", "--bogus"]), &["name"], &[]).is_err());
+        assert!(parse_args("run", &args(&["shop", "--json"]), &["name"], &[]).is_err());
+        assert!(parse_args("run", &[], &["name"], &[]).is_err());
+        assert!(parse_args("stop", &args(&["shop", "blog"]), &["name"], &[]).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::detect::{DetectedProject, PackageManager, ProjectType};
+
+/// pip inside the project's virtualenv, for installing into it.
+#[cfg(windows)]
+const VENV_PIP: &str = r".venv\Scripts\pip";
+#[cfg(not(windows))]
+const VENV_PIP: &str = ".venv/bin/pip";
+
+/// Where the virtualenv keeps `python` and the tools installed into it.
+#[cfg(windows)]
+const VENV_BIN: &str = r".venv\Scripts";
+#[cfg(not(windows))]
+const VENV_BIN: &str = ".venv/bin";
+
+#[cfg(windows)]
+const PYTHON: &str = "python";
+#[cfg(not(windows))]
+const PYTHON: &str = "python3";
+
+/// How a project's dependencies are installed, and what decides whether
+/// installing again is needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Installer {
+    /// Shown in the run progress, e.g. `cargo fetch`
+    pub name: String,
+    pub command: String,
+    /// Manifests and lockfiles; a change to any of them means installing again
+    pub inputs: &'static [&'static str],
+    /// Directory the install creates, which must still exist to skip it
+    pub output: Option<&'static str>,
+}
+
+impl Installer {
+    fn new(command: &str, inputs: &'static [&'static str]) -> Self {
+        Self {
+            name: command.to_string(),
+            command: command.to_string(),
+            inputs,
+            output: None,
+        }
+    }
+
+    /// Hash of the inputs present in `path`. None when there are none, so
+    /// there is nothing to compare and the install always runs.
+    pub fn fingerprint(&self, path: &Path) -> Option<String> {
+        let mut hash = FNV_OFFSET;
+        let mut found = false;
+        for input in self.inputs {
+            if let Ok(content) = fs::read(path.join(input)) {
+                hash = fnv1a(hash, input.as_bytes());
+                hash = fnv1a(hash, &content);
+                found = true;
+            }
+        }
+        found.then(|| format!("{:016x}", hash))
+    }
+
+    /// Whether the last install succeeded with the inputs as they are now,
+    /// and what it created is still there.
+    pub fn is_up_to_date(&self, path: &Path, last: Option<&InstallState>) -> bool {
+        let last = match last {
+            Some(state) if state.ok => state,
+            _ => return false,
+        };
+        self.output.is_none_or(|dir| path.join(dir).is_dir())
+            && self.fingerprint(path).is_some_and(|f| f == last.fingerprint)
+    }
+}
+
+/// How to install the dependencies of the project in `path`: with its JS
+/// package manager, else by the first manifest found.
+pub fn installer(path: &Path, detection: Option<&DetectedProject>) -> Option<Installer> {
+    if let Some(detection) = detection.filter(|d| d.project_type == ProjectType::JavaScript) {
+        let (pm, inputs): (_, &'static [&'static str]) =
+            match detection.package_manager.unwrap_or(PackageManager::Npm) {
+                PackageManager::Pnpm => ("pnpm", &["package.json", "pnpm-lock.yaml"]),
+                PackageManager::Yarn => ("yarn", &["package.json", "yarn.lock"]),
+                PackageManager::Bun => ("bun", &["package.json", "bun.lockb", "bun.lock"]),
+                PackageManager::Npm => ("npm", &["package.json", "package-lock.json"]),
+                _ => return None, // Not a JS package manager
+            };
+        return Some(Installer {
+            output: Some("node_modules"),
+            ..Installer::new(&format!("{} install", pm), inputs)
+        });
+    }
+
+    if path.join("Cargo.toml").exists() {
+        return Some(Installer::new("cargo fetch", &["Cargo.toml", "Cargo.lock"]));
+    }
+    if path.join("go.mod").exists() {
+        return Some(Installer::new("go mod download", &["go.mod", "go.sum"]));
+    }
+    if let Some(installer) = python(path) {
+        return Some(installer);
+    }
+    if path.join("Gemfile").exists() {
+        return Some(Installer::new("bundle install", &["Gemfile", "Gemfile.lock"]));
+    }
+    None
+}
+
+/// The executables directory of the project's `.venv`, when it has one.
+pub fn venv_bin(path: &Path) -> Option<PathBuf> {
+    let bin = path.join(VENV_BIN);
+    bin.is_dir().then_some(bin)
+}
+
+/// uv or poetry when the project uses them, else pip in a `.venv`.
+fn python(path: &Path) -> Option<Installer> {
+    let pyproject = fs::read_to_string(path.join("pyproject.toml")).ok();
+
+    if path.join("uv.lock").exists() {
+        return Some(Installer {
+            output: Some(".venv"),
+            ..Installer::new("uv sync", &["pyproject.toml", "uv.lock"])
+        });
+    }
+    if path.join("poetry.lock").exists() || pyproject.as_deref().is_some_and(|p| p.contains("[tool.poetry]")) {
+        return Some(Installer::new("poetry install", &["pyproject.toml", "poetry.lock"]));
+    }
+
+    let (args, inputs): (&str, &'static [&'static str]) = if path.join("requirements.txt").exists() {
+        ("-r requirements.txt", &["requirements.txt"])
+    } else if pyproject.is_some() {
+        ("-e .", &["pyproject.toml"])
+    } else {
+        return None;
+    };
+    Some(Installer {
+        name: "pip install".to_string(),
+        command: format!("{} -m venv .venv && {} install {}", PYTHON, VENV_PIP, args),
+        inputs,
+        output: Some(".venv"),
+    })
+}
+
+/// What the last install of a project found, saved per project under
+/// `~/.claude-manager/installs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallState {
+    pub fingerprint: String,
+    /// Whether the install succeeded
+    pub ok: bool,
+}
+
+impl InstallState {
+    fn path(project_name: &str) -> Option<PathBuf> {
+        dirs::home_dir().map(|home| {
+            home.join(".claude-manager")
+                .join("installs")
+                .join(format!("{}.toml", project_name))
+        })
+    }
+
+    pub fn load(project_name: &str) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(project_name)?).ok()?;
+        toml::from_str(&content).ok()
+    }
+
+    fn save(&self, project_name: &str) -> Result<()> {
+        let path = match Self::path(project_name) {
+            Some(p) => p,
+            None => bail!("Cannot find home directory"),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// An install whose outcome is to be saved once it has run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallRecord {
+    pub project: String,
+    pub path: PathBuf,
+    pub installer: Installer,
+}
+
+impl InstallRecord {
+    /// Save the outcome, fingerprinting the inputs as the install left them:
+    /// installing may itself write the lockfile.
+    pub fn save(&self, ok: bool) -> Result<()> {
+        let fingerprint = match self.installer.fingerprint(&self.path) {
+            Some(f) => f,
+            None => return Ok(()),
+        };
+        InstallState { fingerprint, ok }.save(&self.project)
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+/// FNV-1a: stable across builds, unlike std's hasher, so saved
+/// fingerprints stay comparable after an upgrade.
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installers_and_fingerprints() {
+        let temp = std::env::temp_dir().join("claude-manager-deps-test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(&temp).unwrap();
+
+        fs::write(temp.join("requirements.txt"), "flask\n").unwrap();
+        let pip = installer(&temp, None).unwrap();
+        assert_eq!(pip.name, "pip install");
+        assert!(pip.command.ends_with("install -r requirements.txt"));
+
+        fs::write(temp.join("Cargo.toml"), "[package]\nname = \"x\"").unwrap();
+        let cargo = installer(&temp, None).unwrap();
+        assert_eq!(cargo.command, "cargo fetch");
+
+        // Up to date only after a successful install with the same inputs
+        let before = cargo.fingerprint(&temp).unwrap();
+        let ok = InstallState { fingerprint: before.clone(), ok: true };
+        assert!(cargo.is_up_to_date(&temp, Some(&ok)));
+        assert!(!cargo.is_up_to_date(&temp, Some(&InstallState { ok: false, ..ok.clone() })));
+        assert!(!cargo.is_up_to_date(&temp, None));
+
+        fs::write(temp.join("Cargo.lock"), "version = 3").unwrap();
+        assert_ne!(cargo.fingerprint(&temp).unwrap(), before);
+        assert!(!cargo.is_up_to_date(&temp, Some(&ok)));
+
+        // What the install creates has to be there too
+        let state = InstallState { fingerprint: pip.fingerprint(&temp).unwrap(), ok: true };
+        assert!(!pip.is_up_to_date(&temp, Some(&state)));
+        fs::create_dir_all(temp.join(".venv")).unwrap();
+        assert!(pip.is_up_to_date(&temp, Some(&state)));
+
+        let _ = fs::remove_dir_all(&temp);
+    }
+
+    #[test]
+    fn test_js_install_needs_node_modules() {
+        let temp = std::env::temp_dir().join("claude-manager-deps-js");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(&temp).unwrap();
+        fs::write(temp.join("package.json"), r#"{"scripts": {"dev": "vite"}}"#).unwrap();
+        fs::write(temp.join("pnpm-lock.yaml"), "lockfileVersion: 9").unwrap();
+
+        let detection = crate::detect::detect(&temp).unwrap();
+        let pnpm = installer(&temp, Some(&detection)).unwrap();
+        assert_eq!(pnpm.command, "pnpm install");
+        let state = InstallState { fingerprint: pnpm.fingerprint(&temp).unwrap(), ok: true };
+
+        // A deleted node_modules means installing again
+        assert!(!pnpm.is_up_to_date(&temp, Some(&state)));
+        fs::create_dir_all(temp.join("node_modules")).unwrap();
+        assert!(pnpm.is_up_to_date(&temp, Some(&state)));
+
+        // So does a new dependency in package.json
+        fs::write(temp.join("package.json"), r#"{"dependencies": {"vite": "5"}}"#).unwrap();
+        assert!(!pnpm.is_up_to_date(&temp, Some(&state)));
+
+        let _ = fs::remove_dir_all(&temp);
+    }
+}
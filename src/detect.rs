@@ -1,25 +1,105 @@
 use anyhow::Result;
-use serde::Deserialize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 
-#[derive(Debug, Clone)]
+use crate::workspace::{self, WorkspacePackage};
+
+#[derive(Debug, Clone, Serialize)]
 pub struct DetectedProject {
     pub package_manager: Option<PackageManager>,
     pub run_command: Option<String>,
     pub project_type: ProjectType,
+    /// Packages of a JS or Cargo workspace, each runnable on its own
+    pub packages: Vec<WorkspacePackage>,
+    /// How the run command is told which port to listen on
+    pub port_style: Option<PortStyle>,
+}
+
+impl DetectedProject {
+    fn with_port(self, style: PortStyle) -> Self {
+        Self {
+            port_style: Some(style),
+            ..self
+        }
+    }
+}
+
+/// How a dev server is told which port to listen on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortStyle {
+    /// An environment variable, usually `PORT`
+    Env(&'static str),
+    /// An argument appended to the command, `{}` standing for the port
+    Arg(&'static str),
+    /// The command ends with a `host:port` address, whose port is replaced
+    Listen,
+}
+
+impl PortStyle {
+    /// The command and extra environment that make `command` listen on `port`.
+    pub fn apply(&self, command: &str, port: u16) -> (String, Vec<(String, String)>) {
+        match *self {
+            PortStyle::Env(var) => (command.to_string(), vec![(var.to_string(), port.to_string())]),
+            PortStyle::Arg(template) => {
+                let arg = template.replace("{}", &port.to_string());
+                // npm only hands the script arguments that come after `--`
+                let separator = match command.starts_with("npm run ") && !command.contains(" -- ") {
+                    true => " -- ",
+                    false => " ",
+                };
+                (format!("{}{}{}", command, separator, arg), Vec::new())
+            }
+            PortStyle::Listen => {
+                let (head, address) = command.rsplit_once(' ').unwrap_or(("", command));
+                let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+                (format!("{} {}:{}", head, host, port).trim_start().to_string(), Vec::new())
+            }
+        }
+    }
+}
+
+/// How a JS dev script takes its port: most tools read `PORT`, but Vite and
+/// the frameworks built on it, Angular and webpack only take a flag.
+pub fn js_port_style(script: &str) -> PortStyle {
+    const PORT_FLAG: &[&str] = &["vite", "astro", "nuxt", "nuxi", "ng", "svelte-kit", "webpack-dev-server"];
+    let tools: Vec<&str> = script
+        .split_whitespace()
+        .map(|w| w.split(':').next().unwrap_or(w))
+        .collect();
+    if tools.iter().any(|t| PORT_FLAG.contains(t)) || script.contains("webpack serve") {
+        PortStyle::Arg("--port {}")
+    } else if tools.contains(&"gatsby") {
+        PortStyle::Arg("-p {}")
+    } else {
+        PortStyle::Env("PORT")
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PackageManager {
     Pnpm,
     Yarn,
     Bun,
     Npm,
+    Deno,
     Cargo,
     Go,
+    /// pip
     Python,
+    Uv,
+    Poetry,
+    Bundler,
+    Composer,
+    Mix,
+    Maven,
+    Gradle,
+    Dotnet,
 }
 
 impl PackageManager {
@@ -29,81 +109,355 @@ impl PackageManager {
             PackageManager::Yarn => "yarn",
             PackageManager::Bun => "bun",
             PackageManager::Npm => "npm",
+            PackageManager::Deno => "deno",
             PackageManager::Cargo => "cargo",
             PackageManager::Go => "go",
             PackageManager::Python => "python",
+            PackageManager::Uv => "uv",
+            PackageManager::Poetry => "poetry",
+            PackageManager::Bundler => "bundler",
+            PackageManager::Composer => "composer",
+            PackageManager::Mix => "mix",
+            PackageManager::Maven => "maven",
+            PackageManager::Gradle => "gradle",
+            PackageManager::Dotnet => "dotnet",
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ProjectType {
     JavaScript,
+    Deno,
     Rust,
     Go,
     Python,
+    Ruby,
+    Php,
+    Elixir,
+    Java,
+    DotNet,
+    /// Only a `docker-compose.yml`
+    Compose,
+    /// Only a `Procfile`
+    Procfile,
+    /// Only a Makefile with a `dev` target
+    Make,
     Unknown,
 }
 
+impl ProjectType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProjectType::JavaScript => "JavaScript",
+            ProjectType::Deno => "Deno",
+            ProjectType::Rust => "Rust",
+            ProjectType::Go => "Go",
+            ProjectType::Python => "Python",
+            ProjectType::Ruby => "Ruby",
+            ProjectType::Php => "PHP",
+            ProjectType::Elixir => "Elixir",
+            ProjectType::Java => "Java/Kotlin",
+            ProjectType::DotNet => ".NET",
+            ProjectType::Compose => "Docker Compose",
+            ProjectType::Procfile => "Procfile",
+            ProjectType::Make => "Make",
+            ProjectType::Unknown => "Unknown",
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct PackageJson {
     scripts: Option<HashMap<String, String>>,
 }
 
+#[derive(Deserialize)]
+struct DenoJson {
+    tasks: Option<HashMap<String, String>>,
+}
+
+/// Detectors in order of precedence: language manifests first, then the
+/// generic ways of running a project.
+const DETECTORS: &[fn(&Path) -> Option<DetectedProject>] = &[
+    detect_deno,
+    detect_rust,
+    detect_go,
+    detect_python,
+    detect_ruby,
+    detect_php,
+    detect_elixir,
+    detect_java,
+    detect_dotnet,
+    detect_procfile,
+    detect_compose,
+    detect_make,
+];
+
 pub fn detect(path: &Path) -> Result<DetectedProject> {
-    // Check for JS project
-    if path.join("package.json").exists() {
+    // Check for JS project (a Deno project may have a package.json too)
+    if path.join("package.json").exists() && !is_deno(path) {
         return detect_js(path);
     }
 
-    // Check for Rust project
-    if path.join("Cargo.toml").exists() {
-        return Ok(DetectedProject {
-            package_manager: Some(PackageManager::Cargo),
-            run_command: Some("cargo run".to_string()),
-            project_type: ProjectType::Rust,
-        });
+    Ok(DETECTORS
+        .iter()
+        .find_map(|detector| detector(path))
+        .unwrap_or_else(|| project(ProjectType::Unknown, None, None)))
+}
+
+fn project(project_type: ProjectType, pm: Option<PackageManager>, run: Option<String>) -> DetectedProject {
+    DetectedProject {
+        package_manager: pm,
+        run_command: run,
+        project_type,
+        packages: Vec::new(),
+        port_style: None,
     }
+}
 
-    // Check for Go project
-    if path.join("go.mod").exists() {
-        return Ok(DetectedProject {
-            package_manager: Some(PackageManager::Go),
-            run_command: Some("go run .".to_string()),
-            project_type: ProjectType::Go,
-        });
+fn is_deno(path: &Path) -> bool {
+    path.join("deno.json").exists() || path.join("deno.jsonc").exists()
+}
+
+fn detect_deno(path: &Path) -> Option<DetectedProject> {
+    if !is_deno(path) {
+        return None;
     }
+    // deno.jsonc may have comments, which serde_json can't read
+    let tasks = fs::read_to_string(path.join("deno.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<DenoJson>(&c).ok())
+        .and_then(|d| d.tasks)
+        .unwrap_or_default();
+    let run = match ["dev", "start"].iter().find(|t| tasks.contains_key(**t)) {
+        Some(task) => Some(format!("deno task {}", task)),
+        None => ["main.ts", "main.js", "server.ts"]
+            .iter()
+            .find(|f| path.join(f).exists())
+            .map(|f| format!("deno run -A {}", f)),
+    };
+    Some(project(ProjectType::Deno, Some(PackageManager::Deno), run).with_port(PortStyle::Env("PORT")))
+}
+
+/// `cargo run`; Rocket apps read their port from `ROCKET_PORT`.
+fn detect_rust(path: &Path) -> Option<DetectedProject> {
+    let manifest = fs::read_to_string(path.join("Cargo.toml")).ok()?;
+    let style = match manifest.contains("rocket") {
+        true => PortStyle::Env("ROCKET_PORT"),
+        false => PortStyle::Env("PORT"),
+    };
+    Some(DetectedProject {
+        packages: workspace::cargo_packages(path),
+        ..project(ProjectType::Rust, Some(PackageManager::Cargo), Some("cargo run".to_string()))
+            .with_port(style)
+    })
+}
 
-    // Check for Python project
-    if path.join("manage.py").exists() {
-        return Ok(DetectedProject {
-            package_manager: Some(PackageManager::Python),
-            run_command: Some("python manage.py runserver".to_string()),
-            project_type: ProjectType::Python,
-        });
+fn detect_go(path: &Path) -> Option<DetectedProject> {
+    path.join("go.mod").exists().then(|| {
+        project(ProjectType::Go, Some(PackageManager::Go), Some("go run .".to_string()))
+            .with_port(PortStyle::Env("PORT"))
+    })
+}
+
+/// Django, FastAPI or Flask apps, or a plain `main.py`, run through uv or
+/// poetry when the project uses them.
+fn detect_python(path: &Path) -> Option<DetectedProject> {
+    let pyproject = fs::read_to_string(path.join("pyproject.toml")).ok();
+    let is_python = pyproject.is_some()
+        || ["manage.py", "main.py", "app.py", "requirements.txt"]
+            .iter()
+            .any(|f| path.join(f).exists());
+    if !is_python {
+        return None;
     }
-    if path.join("main.py").exists() {
-        return Ok(DetectedProject {
-            package_manager: Some(PackageManager::Python),
-            run_command: Some("python main.py".to_string()),
-            project_type: ProjectType::Python,
-        });
+
+    let pm = if path.join("uv.lock").exists() {
+        PackageManager::Uv
+    } else if path.join("poetry.lock").exists()
+        || pyproject.as_deref().is_some_and(|p| p.contains("[tool.poetry]"))
+    {
+        PackageManager::Poetry
+    } else {
+        PackageManager::Python
+    };
+    let prefix = match pm {
+        PackageManager::Uv => "uv run ",
+        PackageManager::Poetry => "poetry run ",
+        _ => "",
+    };
+
+    // `runserver` takes its address as an argument; 0.0.0.0 listens on all interfaces
+    let (run, style) = if path.join("manage.py").exists() {
+        (Some("python manage.py runserver".to_string()), PortStyle::Arg("0.0.0.0:{}"))
+    } else if let Some(app) = python_app(path) {
+        (Some(app), PortStyle::Arg("--port {}"))
+    } else {
+        let main = path.join("main.py").exists().then(|| "python main.py".to_string());
+        (main, PortStyle::Env("PORT"))
+    };
+    let run = run.map(|r| format!("{}{}", prefix, r));
+    Some(project(ProjectType::Python, Some(pm), run).with_port(style))
+}
+
+/// A FastAPI or Flask app object in one of the usual entrypoints.
+fn python_app(path: &Path) -> Option<String> {
+    static APPS: OnceLock<(Regex, Regex)> = OnceLock::new();
+    let (fastapi, flask) = APPS.get_or_init(|| {
+        (
+            Regex::new(r"(?m)^(\w+)\s*=\s*FastAPI\(").unwrap(),
+            Regex::new(r"(?m)^(\w+)\s*=\s*Flask\(").unwrap(),
+        )
+    });
+    for (file, module) in [("main.py", "main"), ("app.py", "app"), ("app/main.py", "app.main")] {
+        let source = match fs::read_to_string(path.join(file)) {
+            Ok(source) => source,
+            Err(_) => continue,
+        };
+        if let Some(caps) = fastapi.captures(&source) {
+            return Some(format!("uvicorn {}:{} --reload", module, &caps[1]));
+        }
+        if let Some(caps) = flask.captures(&source) {
+            return Some(format!("flask --app {}:{} run --debug", module, &caps[1]));
+        }
     }
+    None
+}
 
-    Ok(DetectedProject {
-        package_manager: None,
-        run_command: None,
-        project_type: ProjectType::Unknown,
+fn detect_ruby(path: &Path) -> Option<DetectedProject> {
+    if !path.join("Gemfile").exists() {
+        return None;
+    }
+    let run = if path.join("bin/rails").exists() {
+        Some("bin/rails server".to_string())
+    } else if path.join("config.ru").exists() {
+        Some("bundle exec rackup".to_string())
+    } else {
+        None
+    };
+    Some(project(ProjectType::Ruby, Some(PackageManager::Bundler), run).with_port(PortStyle::Arg("-p {}")))
+}
+
+fn detect_php(path: &Path) -> Option<DetectedProject> {
+    if !path.join("composer.json").exists() {
+        return None;
+    }
+    let detected = project(ProjectType::Php, Some(PackageManager::Composer), None);
+    if path.join("artisan").exists() {
+        Some(DetectedProject {
+            run_command: Some("php artisan serve".to_string()),
+            ..detected.with_port(PortStyle::Arg("--port={}"))
+        })
+    } else if path.join("public").is_dir() {
+        Some(DetectedProject {
+            run_command: Some("php -t public -S localhost:8000".to_string()),
+            ..detected.with_port(PortStyle::Listen)
+        })
+    } else {
+        Some(detected)
+    }
+}
+
+fn detect_elixir(path: &Path) -> Option<DetectedProject> {
+    let mix = fs::read_to_string(path.join("mix.exs")).ok()?;
+    let run = match mix.contains(":phoenix") {
+        true => "mix phx.server",
+        false => "mix run --no-halt",
+    };
+    Some(
+        project(ProjectType::Elixir, Some(PackageManager::Mix), Some(run.to_string()))
+            .with_port(PortStyle::Env("PORT")),
+    )
+}
+
+/// Maven or Gradle, preferring the project's wrapper script, with Spring
+/// Boot's run task when the build uses it. Spring Boot reads its port from
+/// `SERVER_PORT`.
+fn detect_java(path: &Path) -> Option<DetectedProject> {
+    if let Ok(pom) = fs::read_to_string(path.join("pom.xml")) {
+        let mvn = if path.join("mvnw").exists() { "./mvnw" } else { "mvn" };
+        let (goal, style) = match pom.contains("spring-boot") {
+            true => ("spring-boot:run", PortStyle::Env("SERVER_PORT")),
+            false => ("compile exec:java", PortStyle::Env("PORT")),
+        };
+        return Some(
+            project(ProjectType::Java, Some(PackageManager::Maven), Some(format!("{} {}", mvn, goal)))
+                .with_port(style),
+        );
+    }
+
+    let build = ["build.gradle", "build.gradle.kts"]
+        .iter()
+        .find_map(|f| fs::read_to_string(path.join(f)).ok())?;
+    let gradle = if path.join("gradlew").exists() { "./gradlew" } else { "gradle" };
+    let (task, style) = match build.contains("org.springframework.boot") {
+        true => ("bootRun", PortStyle::Env("SERVER_PORT")),
+        false => ("run", PortStyle::Env("PORT")),
+    };
+    Some(
+        project(ProjectType::Java, Some(PackageManager::Gradle), Some(format!("{} {}", gradle, task)))
+            .with_port(style),
+    )
+}
+
+fn detect_dotnet(path: &Path) -> Option<DetectedProject> {
+    let has_csproj = fs::read_dir(path)
+        .ok()?
+        .flatten()
+        .any(|e| e.path().extension().is_some_and(|ext| ext == "csproj"));
+    has_csproj.then(|| {
+        project(ProjectType::DotNet, Some(PackageManager::Dotnet), Some("dotnet run".to_string()))
+            .with_port(PortStyle::Arg("--urls http://localhost:{}"))
     })
 }
 
+/// The `web` process of a Procfile, else its first one.
+fn detect_procfile(path: &Path) -> Option<DetectedProject> {
+    let procfile = fs::read_to_string(path.join("Procfile")).ok()?;
+    let processes: Vec<(&str, &str)> = procfile
+        .lines()
+        .filter(|l| !l.trim_start().starts_with('#'))
+        .filter_map(|l| l.split_once(':'))
+        .map(|(name, command)| (name.trim(), command.trim()))
+        .filter(|(name, command)| !name.is_empty() && !command.is_empty())
+        .collect();
+    let run = processes
+        .iter()
+        .find(|(name, _)| *name == "web")
+        .or(processes.first())
+        .map(|(_, command)| command.to_string());
+    // Procfiles are written for platforms that hand out `PORT`
+    Some(project(ProjectType::Procfile, None, run).with_port(PortStyle::Env("PORT")))
+}
+
+fn detect_compose(path: &Path) -> Option<DetectedProject> {
+    ["compose.yaml", "compose.yml", "docker-compose.yml", "docker-compose.yaml"]
+        .iter()
+        .any(|f| path.join(f).exists())
+        .then(|| project(ProjectType::Compose, None, Some("docker compose up".to_string())))
+}
+
+fn detect_make(path: &Path) -> Option<DetectedProject> {
+    let makefile = ["Makefile", "makefile", "GNUmakefile"]
+        .iter()
+        .find_map(|f| fs::read_to_string(path.join(f)).ok())?;
+    static DEV: OnceLock<Regex> = OnceLock::new();
+    let dev = DEV.get_or_init(|| Regex::new(r"(?m)^dev\s*:").unwrap());
+    dev.is_match(&makefile)
+        .then(|| {
+            project(ProjectType::Make, None, Some("make dev".to_string())).with_port(PortStyle::Env("PORT"))
+        })
+}
+
 fn detect_js(path: &Path) -> Result<DetectedProject> {
     // Detect package manager from lockfile
     let pm = if path.join("pnpm-lock.yaml").exists() {
         PackageManager::Pnpm
     } else if path.join("yarn.lock").exists() {
         PackageManager::Yarn
-    } else if path.join("bun.lockb").exists() {
+    } else if path.join("bun.lockb").exists() || path.join("bun.lock").exists() {
         PackageManager::Bun
     } else {
         PackageManager::Npm
@@ -114,22 +468,27 @@ fn detect_js(path: &Path) -> Result<DetectedProject> {
     let content = fs::read_to_string(&pkg_path)?;
     let pkg: PackageJson = serde_json::from_str(&content)?;
 
-    let run_cmd = if let Some(scripts) = pkg.scripts {
+    let (run_cmd, port_style) = if let Some(scripts) = pkg.scripts {
         // Preference order: dev > start > serve > watch
         let script = ["dev", "start", "serve", "watch"]
             .iter()
             .find(|s| scripts.contains_key(**s))
             .copied();
 
-        script.map(|s| format!("{} run {}", pm.as_str(), s))
+        (
+            script.map(|s| format!("{} run {}", pm.as_str(), s)),
+            js_port_style(script.and_then(|s| scripts.get(s)).map_or("", |s| s.as_str())),
+        )
     } else {
-        None
+        (None, PortStyle::Env("PORT"))
     };
 
     Ok(DetectedProject {
         package_manager: Some(pm),
         run_command: run_cmd,
         project_type: ProjectType::JavaScript,
+        packages: workspace::js_packages(path, pm),
+        port_style: Some(port_style),
     })
 }
 
@@ -137,6 +496,21 @@ fn detect_js(path: &Path) -> Result<DetectedProject> {
 mod tests {
     use super::*;
 
+    /// Detect a project made of `files`, written to a fresh `dir` under the
+    /// temp directory.
+    fn detect_files(dir: &str, files: &[(&str, &str)]) -> DetectedProject {
+        let temp = std::env::temp_dir().join(dir);
+        let _ = fs::remove_dir_all(&temp);
+        for (file, content) in files {
+            let path = temp.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let detected = detect(&temp).unwrap();
+        let _ = fs::remove_dir_all(&temp);
+        detected
+    }
+
     #[test]
     fn test_detect_js_with_pnpm() {
         let temp = std::env::temp_dir().join("claude-manager-detect-test");
@@ -173,4 +547,97 @@ mod tests {
 
         let _ = fs::remove_dir_all(&temp);
     }
+
+    #[test]
+    fn test_detect_other_ecosystems() {
+        let detect_with = |files: &[(&str, &str)]| {
+            let detected = detect_files("claude-manager-detect-more", files);
+            (detected.project_type, detected.run_command.unwrap_or_default())
+        };
+
+        assert_eq!(
+            detect_with(&[("deno.json", r#"{"tasks": {"dev": "deno run -A main.ts"}}"#), ("package.json", "{}")]),
+            (ProjectType::Deno, "deno task dev".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("Gemfile", ""), ("bin/rails", "")]),
+            (ProjectType::Ruby, "bin/rails server".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("composer.json", "{}"), ("artisan", "")]),
+            (ProjectType::Php, "php artisan serve".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("mix.exs", "{:phoenix, \"~> 1.7\"}")]),
+            (ProjectType::Elixir, "mix phx.server".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("build.gradle.kts", "id(\"org.springframework.boot\")"), ("gradlew", "")]),
+            (ProjectType::Java, "./gradlew bootRun".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("Api.csproj", "<Project/>")]),
+            (ProjectType::DotNet, "dotnet run".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("pyproject.toml", ""), ("uv.lock", ""), ("main.py", "api = FastAPI()\n")]),
+            (ProjectType::Python, "uv run uvicorn main:api --reload".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("app.py", "app = Flask(__name__)\n")]),
+            (ProjectType::Python, "flask --app app:app run --debug".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("Procfile", "worker: bin/worker\nweb: bin/server -p $PORT\n")]),
+            (ProjectType::Procfile, "bin/server -p $PORT".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("compose.yaml", "services: {}")]),
+            (ProjectType::Compose, "docker compose up".to_string())
+        );
+        assert_eq!(
+            detect_with(&[("Makefile", "build:\n\tgo build\ndev: build\n\t./server\n")]),
+            (ProjectType::Make, "make dev".to_string())
+        );
+    }
+
+    #[test]
+    fn test_port_styles() {
+        let run_on = |files: &[(&str, &str)], port: u16| {
+            let detected = detect_files("claude-manager-detect-ports", files);
+            detected.port_style.map(|style| style.apply(&detected.run_command.unwrap(), port))
+        };
+        let cmd = |c: &str| Some((c.to_string(), Vec::new()));
+        let env = |c: &str, k: &str, v: &str| Some((c.to_string(), vec![(k.to_string(), v.to_string())]));
+
+        assert_eq!(
+            run_on(&[("package.json", r#"{"scripts": {"dev": "vite"}}"#), ("package-lock.json", "")], 5174),
+            cmd("npm run dev -- --port 5174")
+        );
+        assert_eq!(
+            run_on(&[("package.json", r#"{"scripts": {"dev": "next dev"}}"#), ("pnpm-lock.yaml", "")], 3001),
+            env("pnpm run dev", "PORT", "3001")
+        );
+        assert_eq!(
+            run_on(&[("manage.py", ""), ("requirements.txt", "django")], 8001),
+            cmd("python manage.py runserver 0.0.0.0:8001")
+        );
+        assert_eq!(
+            run_on(&[("Gemfile", ""), ("bin/rails", "")], 3002),
+            cmd("bin/rails server -p 3002")
+        );
+        assert_eq!(
+            run_on(&[("composer.json", "{}"), ("public/index.php", "")], 8002),
+            cmd("php -t public -S localhost:8002")
+        );
+        assert_eq!(
+            run_on(&[("pom.xml", "<artifactId>spring-boot-starter-web</artifactId>")], 8081),
+            env("mvn spring-boot:run", "SERVER_PORT", "8081")
+        );
+        assert_eq!(
+            run_on(&[("Cargo.toml", "[dependencies]\nrocket = \"0.5\"\n")], 8003),
+            env("cargo run", "ROCKET_PORT", "8003")
+        );
+        assert_eq!(run_on(&[("compose.yaml", "services: {}")], 8004), None);
+    }
 }
@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// One variable from a dotenv file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DotenvVar {
+    pub key: String,
+    pub value: String,
+    /// Single-quoted: taken as written, without `${VAR}` expansion
+    pub literal: bool,
+}
+
+/// Parse a dotenv file: `KEY=VALUE` lines with optional `export` prefix,
+/// single or double quotes, and `#` comments.
+pub fn parse_dotenv(content: &str) -> Vec<DotenvVar> {
+    let mut vars = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let (key, value) = match line.split_once('=') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => continue,
+        };
+        if key.is_empty() {
+            continue;
+        }
+
+        let literal = value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'');
+        let value = if let Some(inner) = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+        {
+            inner.replace("\\n", "\n").replace("\\\"", "\"")
+        } else if literal {
+            value[1..value.len() - 1].to_string()
+        } else {
+            // Unquoted values end at an inline comment
+            match value.find(" #") {
+                Some(idx) => value[..idx].trim_end().to_string(),
+                None => value.to_string(),
+            }
+        };
+
+        vars.push(DotenvVar {
+            key: key.to_string(),
+            value,
+            literal,
+        });
+    }
+
+    vars
+}
+
+/// Expand `${VAR}` references. Unknown variables expand to an empty string,
+/// as they would in a shell.
+pub fn interpolate(value: &str, mut lookup: impl FnMut(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                out.push_str(&lookup(&after[..end]).unwrap_or_default());
+                rest = &after[end + 1..];
+            }
+            None => {
+                // Unterminated reference: keep it literally
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Resolve a project's environment: each dotenv file in order, then the `env`
+/// table, later values winning. File values may reference variables defined
+/// earlier; table values may reference any variable of the result. Both fall
+/// back to this process's environment. Missing files are skipped.
+pub fn resolve(
+    project_path: &Path,
+    env_files: &[String],
+    env: &BTreeMap<String, String>,
+) -> Vec<(String, String)> {
+    let mut resolved: Vec<(String, String)> = Vec::new();
+
+    let file_vars = env_files.iter().flat_map(|file| {
+        fs::read_to_string(project_path.join(file))
+            .map(|content| parse_dotenv(&content))
+            .unwrap_or_default()
+    });
+    for var in file_vars {
+        if var.literal {
+            put(&mut resolved, &var.key, var.value);
+        } else {
+            set(&mut resolved, &var.key, &var.value);
+        }
+    }
+    set_table(&mut resolved, env);
+
+    resolved
+}
+
+/// Interpolate `raw` against `vars` (falling back to this process's
+/// environment) and set `key` to the result, replacing any earlier value.
+pub fn set(vars: &mut Vec<(String, String)>, key: &str, raw: &str) {
+    let value = interpolate(raw, |name| lookup(vars, name));
+    put(vars, key, value);
+}
+
+/// Interpolate and set every entry of an `env` table. A reference to another
+/// entry sees that entry's final value, whatever order the keys sort in; a
+/// reference to a variable not in the table, or to the entry itself, sees
+/// `vars`.
+pub fn set_table(vars: &mut Vec<(String, String)>, table: &BTreeMap<String, String>) {
+    let mut done = BTreeMap::new();
+    for key in table.keys() {
+        resolve_entry(key, table, vars, &mut done, &mut Vec::new());
+    }
+    for (key, value) in done {
+        put(vars, &key, value);
+    }
+}
+
+/// Value of one `env` table entry, resolving the entries it references first.
+/// `visiting` holds the entries being resolved, so a cycle falls back to
+/// `vars` instead of recursing forever.
+fn resolve_entry(
+    key: &str,
+    table: &BTreeMap<String, String>,
+    vars: &[(String, String)],
+    done: &mut BTreeMap<String, String>,
+    visiting: &mut Vec<String>,
+) -> String {
+    if let Some(value) = done.get(key) {
+        return value.clone();
+    }
+    visiting.push(key.to_string());
+    let value = interpolate(&table[key], |name| {
+        if table.contains_key(name) && !visiting.iter().any(|k| k == name) {
+            Some(resolve_entry(name, table, vars, done, visiting))
+        } else {
+            lookup(vars, name)
+        }
+    });
+    visiting.pop();
+    done.insert(key.to_string(), value.clone());
+    value
+}
+
+fn lookup(vars: &[(String, String)], name: &str) -> Option<String> {
+    vars.iter()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.clone())
+        .or_else(|| std::env::var(name).ok())
+}
+
+/// Set `key`, replacing any earlier value.
+fn put(vars: &mut Vec<(String, String)>, key: &str, value: String) {
+    match vars.iter_mut().find(|(k, _)| k == key) {
+        Some(existing) => existing.1 = value,
+        None => vars.push((key.to_string(), value)),
+    }
+}
+
+/// Put a virtualenv's executables directory first on `PATH` and point
+/// `VIRTUAL_ENV` at it, as sourcing its `activate` script would.
+pub fn activate_venv(vars: &mut Vec<(String, String)>, bin: &Path) {
+    let path = lookup(vars, "PATH").unwrap_or_default();
+    let dirs = std::iter::once(bin.to_path_buf()).chain(std::env::split_paths(&path));
+    if let Ok(joined) = std::env::join_paths(dirs) {
+        put(vars, "PATH", joined.to_string_lossy().to_string());
+    }
+    if let Some(venv) = bin.parent() {
+        put(vars, "VIRTUAL_ENV", venv.to_string_lossy().to_string());
+    }
+}
+
+/// Variables that make most CLIs keep their colors when output is piped.
+const FORCE_COLOR_VARS: &[&str] = &["FORCE_COLOR", "CLICOLOR_FORCE"];
+
+/// Turn on colored output, unless the configuration already decides it.
+pub fn force_color(vars: &mut Vec<(String, String)>) {
+    for name in FORCE_COLOR_VARS {
+        if !vars.iter().any(|(k, _)| k == name) {
+            vars.push((name.to_string(), "1".to_string()));
+        }
+    }
+}
+
+/// Whether a variable name looks like it holds a credential.
+pub fn is_secret(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    [
+        "SECRET", "TOKEN", "PASSWORD", "PASSWD", "PASS", "KEY", "CREDENTIAL", "PRIVATE", "AUTH",
+    ]
+    .iter()
+    .any(|marker| key.contains(marker))
+}
+
+/// Value for display: secrets are hidden entirely, and passwords embedded in
+/// URLs (`postgres://user:pw@host/db`) are masked everywhere.
+pub fn mask(key: &str, value: &str) -> String {
+    if is_secret(key) && !value.is_empty() {
+        return "********".to_string();
+    }
+
+    if let Some(scheme_end) = value.find("://") {
+        let rest = &value[scheme_end + 3..];
+        let authority_end = rest.find('/').unwrap_or(rest.len());
+        if let Some(at) = rest[..authority_end].rfind('@') {
+            if let Some(colon) = rest[..at].find(':') {
+                return format!(
+                    "{}{}:****{}",
+                    &value[..scheme_end + 3],
+                    &rest[..colon],
+                    &rest[at..]
+                );
+            }
+        }
+    }
+
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = r#"
+# comment
+export API_URL=http://localhost:8080
+NAME="hello world"
+RAW='${NOT_EXPANDED}'
+DEBUG=1 # inline comment
+"#;
+        let vars = parse_dotenv(content);
+        let vars: Vec<(&str, &str, bool)> = vars
+            .iter()
+            .map(|v| (v.key.as_str(), v.value.as_str(), v.literal))
+            .collect();
+        assert_eq!(
+            vars,
+            vec![
+                ("API_URL", "http://localhost:8080", false),
+                ("NAME", "hello world", false),
+                ("RAW", "${NOT_EXPANDED}", true),
+                ("DEBUG", "1", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_interpolates_and_overrides() {
+        let temp = std::env::temp_dir().join("claude-manager-env-test");
+        let _ = fs::remove_dir_all(&temp);
+        fs::create_dir_all(&temp).unwrap();
+        fs::write(
+            temp.join(".env"),
+            "DB_HOST=localhost\nDB_PORT=5432\nRAW='${DB_HOST}'\n",
+        )
+        .unwrap();
+
+        let mut env = BTreeMap::new();
+        env.insert(
+            "DATABASE_URL".to_string(),
+            "postgres://app:pw@${DB_HOST}:${DB_PORT}/app".to_string(),
+        );
+        env.insert("DB_PORT".to_string(), "6543".to_string());
+
+        let files = vec![".env".to_string(), ".env.missing".to_string()];
+        let vars = resolve(&temp, &files, &env);
+        let get = |k: &str| vars.iter().find(|(key, _)| key == k).map(|(_, v)| v.as_str());
+
+        // The table overrides the file, and DATABASE_URL sees the override
+        // even though it sorts first
+        assert_eq!(get("DB_PORT"), Some("6543"));
+        assert_eq!(get("DATABASE_URL"), Some("postgres://app:pw@localhost:6543/app"));
+        // Single-quoted values are not expanded
+        assert_eq!(get("RAW"), Some("${DB_HOST}"));
+
+        let _ = fs::remove_dir_all(&temp);
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(mask("STRIPE_SECRET_KEY", "sk_live_123"), "********");
+        assert_eq!(
+            mask("DATABASE_URL", "postgres://app:hunter2@db:5432/app"),
+            "postgres://app:****@db:5432/app"
+        );
+        assert_eq!(mask("NODE_ENV", "development"), "development");
+    }
+}
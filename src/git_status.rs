@@ -1,8 +1,9 @@
 use anyhow::Result;
+use serde::Serialize;
 use std::path::Path;
 use std::process::Command;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct GitStatus {
     pub branch: String,
     pub staged: usize,
@@ -64,7 +65,7 @@ pub fn get_status(path: &Path) -> Result<GitStatus> {
     if let Ok(output) = output {
         if output.status.success() {
             let counts = String::from_utf8_lossy(&output.stdout);
-            let parts: Vec<&str> = counts.trim().split_whitespace().collect();
+            let parts: Vec<&str> = counts.split_whitespace().collect();
             if parts.len() == 2 {
                 status.behind = parts[0].parse().unwrap_or(0);
                 status.ahead = parts[1].parse().unwrap_or(0);
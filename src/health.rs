@@ -0,0 +1,315 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::store::HealthCheck;
+
+/// Seconds between checks when a project doesn't set `interval`.
+pub const DEFAULT_INTERVAL: u64 = 5;
+
+/// Seconds a check may take when a project doesn't set `timeout`.
+pub const DEFAULT_TIMEOUT: u64 = 2;
+
+/// Seconds a process has to pass its first check when a project doesn't set
+/// `start_period`. Generous, since a first build can take a while.
+pub const DEFAULT_START_PERIOD: u64 = 60;
+
+/// What the health check says about a running process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Health {
+    /// Not passed a check yet, but still within the start period
+    Starting {
+        /// Why the latest check failed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_error: Option<String>,
+    },
+    Ready,
+    /// Failing after having passed or after the start period, or the check
+    /// itself is misconfigured
+    Unhealthy { reason: String },
+}
+
+impl Health {
+    pub fn is_starting(&self) -> bool {
+        matches!(self, Health::Starting { .. })
+    }
+}
+
+/// What a check looks at.
+enum Probe {
+    /// GET this path on the process's port
+    Http(String),
+    Tcp,
+    /// A line of output matching this
+    Log(Regex),
+}
+
+/// Runs one process's health check and tracks the outcome. Network checks
+/// run on a thread; `poll` picks up their results.
+pub struct Monitor {
+    probe: Result<Probe, String>,
+    interval: Duration,
+    timeout: Duration,
+    state: Health,
+    /// Failing checks make the process unhealthy from then on, even if it
+    /// never passed one
+    start_deadline: Instant,
+    next_at: Instant,
+    pending: Option<Receiver<Result<(), String>>>,
+    /// Output lines already searched by a log check
+    seen: usize,
+}
+
+impl Monitor {
+    /// A monitor for a run whose output starts at line `seen`.
+    pub fn new(check: &HealthCheck, seen: usize) -> Self {
+        let probe = match (&check.http, check.tcp, &check.log) {
+            (Some(path), _, _) => Ok(Probe::Http(path.clone())),
+            (None, true, _) => Ok(Probe::Tcp),
+            (None, false, Some(pattern)) => Regex::new(pattern)
+                .map(Probe::Log)
+                .map_err(|e| format!("invalid log pattern: {}", e)),
+            (None, false, None) => Err("no check set: use http, tcp or log".to_string()),
+        };
+        Self {
+            probe,
+            interval: Duration::from_secs(check.interval.unwrap_or(DEFAULT_INTERVAL).max(1)),
+            timeout: Duration::from_secs(check.timeout.unwrap_or(DEFAULT_TIMEOUT).max(1)),
+            state: Health::Starting { last_error: None },
+            start_deadline: Instant::now()
+                + Duration::from_secs(check.start_period.unwrap_or(DEFAULT_START_PERIOD)),
+            next_at: Instant::now(),
+            pending: None,
+            seen,
+        }
+    }
+
+    pub fn state(&self) -> &Health {
+        &self.state
+    }
+
+    /// Advance the check: take in a finished check's result, match new
+    /// output, or start the next check once it is due. `output` returns the
+    /// lines after a given absolute line number and the new total.
+    pub fn poll(&mut self, port: Option<u16>, output: impl FnOnce(usize) -> (Vec<String>, usize)) {
+        let path = match self.probe {
+            Ok(Probe::Http(ref path)) => Some(path.clone()),
+            Ok(Probe::Tcp) => None,
+            Ok(Probe::Log(ref pattern)) => {
+                // A readiness signal: once seen, the process stays ready
+                if self.state != Health::Ready {
+                    let (lines, total) = output(self.seen);
+                    self.seen = total;
+                    if lines.iter().any(|line| pattern.is_match(line)) {
+                        self.state = Health::Ready;
+                    } else if self.state.is_starting() && Instant::now() >= self.start_deadline {
+                        self.state = Health::Unhealthy {
+                            reason: format!("no output matched `{}`", pattern),
+                        };
+                    }
+                }
+                return;
+            }
+            Err(ref reason) => {
+                self.state = Health::Unhealthy { reason: reason.clone() };
+                return;
+            }
+        };
+
+        if let Some(ref rx) = self.pending {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.pending = None;
+                    self.record(result);
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => self.pending = None,
+            }
+        }
+
+        let port = match port {
+            Some(port) if Instant::now() >= self.next_at => port,
+            Some(_) => return,
+            None => {
+                self.record(Err("no port to check".to_string()));
+                return;
+            }
+        };
+        self.next_at = Instant::now() + self.interval;
+        let (tx, rx) = mpsc::channel();
+        let timeout = self.timeout;
+        thread::spawn(move || {
+            let result = match path {
+                Some(path) => http_get(port, &path, timeout),
+                None => connect(port, timeout).map(|_| ()),
+            };
+            let _ = tx.send(result);
+        });
+        self.pending = Some(rx);
+    }
+
+    /// Failures only count as unhealthy once the process has been ready, or
+    /// the start period is over; until then it is still starting.
+    fn record(&mut self, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.state = Health::Ready,
+            Err(reason) if self.state.is_starting() && Instant::now() < self.start_deadline => {
+                self.state = Health::Starting {
+                    last_error: Some(reason),
+                }
+            }
+            Err(reason) => self.state = Health::Unhealthy { reason },
+        }
+    }
+}
+
+/// Connect to `port` on localhost, over IPv4 or IPv6, whichever the server
+/// bound.
+fn connect(port: u16, timeout: Duration) -> Result<TcpStream, String> {
+    let addrs = ("localhost", port).to_socket_addrs().map_err(|e| e.to_string())?;
+    let mut error = "cannot resolve localhost".to_string();
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => error = e.to_string(),
+        }
+    }
+    Err(error)
+}
+
+/// GET `path` and pass on a 2xx or 3xx response.
+fn http_get(port: u16, path: &str, timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(port, timeout)?;
+    stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    let path = if path.starts_with('/') { path.to_string() } else { format!("/{}", path) };
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost:{}\r\nConnection: close\r\n\r\n",
+        path, port
+    );
+    stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+
+    // Only the status line matters, but reading the rest spares the server
+    // a broken pipe
+    let mut head = [0u8; 64];
+    let n = stream.read(&mut head).map_err(|e| e.to_string())?;
+    let _ = std::io::copy(&mut stream.take(1 << 20), &mut std::io::sink());
+    let status = String::from_utf8_lossy(&head[..n])
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok());
+    match status {
+        Some(code) if (200..400).contains(&code) => Ok(()),
+        Some(code) => Err(format!("HTTP {}", code)),
+        None => Err("not an HTTP response".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn wait_for(monitor: &mut Monitor, port: u16, state: &Health) {
+        for _ in 0..50 {
+            monitor.poll(Some(port), |seen| (Vec::new(), seen));
+            if monitor.state() == state {
+                return;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        panic!("expected {:?}, got {:?}", state, monitor.state());
+    }
+
+    #[test]
+    fn test_health_checks() {
+        // HTTP: ready on a 200, then unhealthy once the server answers 503
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            for status in ["200 OK", "503 Service Unavailable"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 256];
+                let _ = stream.read(&mut request);
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+            }
+        });
+        let check = HealthCheck {
+            http: Some("/health".to_string()),
+            interval: Some(1),
+            ..Default::default()
+        };
+        let mut monitor = Monitor::new(&check, 0);
+        assert!(monitor.state().is_starting());
+        wait_for(&mut monitor, port, &Health::Ready);
+        monitor.next_at = Instant::now();
+        let unhealthy = Health::Unhealthy { reason: "HTTP 503".to_string() };
+        wait_for(&mut monitor, port, &unhealthy);
+        server.join().unwrap();
+
+        // Log: ready once a line matches, looking only at new output
+        let check = HealthCheck {
+            log: Some(r"ready in \d+ ?ms".to_string()),
+            ..Default::default()
+        };
+        let output = ["compiling...", "VITE ready in 300 ms"].map(String::from);
+        let mut monitor = Monitor::new(&check, 2);
+        monitor.poll(None, |seen| (output[seen.min(2)..].to_vec(), 2));
+        assert!(monitor.state().is_starting());
+        let mut monitor = Monitor::new(&check, 0);
+        monitor.poll(None, |seen| (output[seen..].to_vec(), 2));
+        assert_eq!(monitor.state(), &Health::Ready);
+    }
+
+    #[test]
+    fn test_failing_check_times_out_of_starting() {
+        // HTTP: a path that is always 404 is starting, with the error kept,
+        // until the start period is over
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 256];
+                let _ = stream.read(&mut request);
+                let _ = write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+            }
+        });
+        let check = HealthCheck {
+            http: Some("/missing".to_string()),
+            ..Default::default()
+        };
+        let mut monitor = Monitor::new(&check, 0);
+        let starting = Health::Starting {
+            last_error: Some("HTTP 404".to_string()),
+        };
+        wait_for(&mut monitor, port, &starting);
+        monitor.start_deadline = Instant::now();
+        monitor.next_at = Instant::now();
+        let unhealthy = Health::Unhealthy { reason: "HTTP 404".to_string() };
+        wait_for(&mut monitor, port, &unhealthy);
+        server.join().unwrap();
+
+        // Log: a pattern that never matched, and then does
+        let check = HealthCheck {
+            log: Some("ready".to_string()),
+            start_period: Some(0),
+            ..Default::default()
+        };
+        let mut monitor = Monitor::new(&check, 0);
+        monitor.poll(None, |_| (vec!["compiling...".to_string()], 1));
+        assert_eq!(
+            monitor.state(),
+            &Health::Unhealthy {
+                reason: "no output matched `ready`".to_string()
+            }
+        );
+        monitor.poll(None, |_| (vec!["ready".to_string()], 2));
+        assert_eq!(monitor.state(), &Health::Ready);
+    }
+}
@@ -0,0 +1,240 @@
+use std::path::Path;
+
+use crate::deps;
+use crate::detect::DetectedProject;
+use crate::env;
+use crate::ports::{self, PortInfo};
+use crate::process::LaunchSpec;
+use crate::store::{ProjectEntry, ProjectStore, ServiceEntry};
+
+/// A process to start for a project: its dev server or one of its services.
+#[derive(Debug, Clone)]
+pub struct Target {
+    /// Process key: the project name, or `project/service`
+    pub key: String,
+    pub spec: LaunchSpec,
+}
+
+/// Command for the project's own dev server: the run-command override, else the
+/// detected command.
+///
+/// A project with services only gets a dev server when the override is set:
+/// declaring services usually means the root command isn't the one to run.
+pub fn dev_server_command(
+    project: &ProjectEntry,
+    detection: Option<&DetectedProject>,
+) -> Option<String> {
+    match project.run_command {
+        Some(ref cmd) => Some(cmd.clone()),
+        None if project.services.is_empty() => detection?.run_command.clone(),
+        None => None,
+    }
+}
+
+/// The project's own dev server, if it has a command. Projects whose
+/// framework takes a port get their own port, or a free one from the
+/// configured ranges that no other project has reserved, passed the way the
+/// framework expects it.
+pub fn dev_server(
+    project: &ProjectEntry,
+    detection: Option<&DetectedProject>,
+    store: &ProjectStore,
+) -> Option<Target> {
+    let mut command = dev_server_command(project, detection)?;
+    let mut vars = launch_env(project);
+
+    let style = detection.and_then(|d| d.port_style);
+    let port = style.and_then(|_| {
+        let reserved = store.ports_reserved_by_others(&project.name);
+        store.ports.assign(project.port, project.pin_port, &reserved)
+    });
+    if let (Some(style), Some(port)) = (style, port) {
+        // An override is the user's own command: only its environment is ours to set
+        let (styled, extra) = style.apply(&command, port);
+        if project.run_command.is_none() {
+            command = styled;
+        }
+        for (key, value) in extra {
+            env::set(&mut vars, &key, &value);
+        }
+    }
+
+    Some(Target {
+        key: project.name.clone(),
+        spec: LaunchSpec {
+            env: vars,
+            port,
+            shell: project.shell,
+            pty: project.pty,
+            restart: project.restart,
+            max_restarts: project.max_restarts,
+            stop_timeout: project.stop_timeout,
+            health: project.health.clone(),
+            ..LaunchSpec::new(Path::new(&project.path), &command)
+        },
+    })
+}
+
+/// What holds the project's pinned port, when its dev server is about to
+/// need it and can't have it.
+pub fn pinned_port_holder(project: &ProjectEntry, detection: Option<&DetectedProject>) -> Option<PortInfo> {
+    let port = project.port.filter(|_| project.pin_port)?;
+    detection?.port_style?;
+    dev_server_command(project, detection)?;
+    ports::holder(port)
+}
+
+/// What holds a service's port before it starts. Its command decides the
+/// port, so any holder is in the way.
+pub fn service_port_holder(service: &ServiceEntry) -> Option<PortInfo> {
+    ports::holder(service.port?)
+}
+
+/// One named service of a project. It inherits the project's environment, and
+/// its own `env` table may reference those values.
+pub fn service(project: &ProjectEntry, service: &ServiceEntry) -> Target {
+    let cwd = service.resolve_cwd(Path::new(&project.path));
+
+    let mut vars = launch_env(project);
+    env::set_table(&mut vars, &service.env);
+
+    Target {
+        key: project.service_key(&service.name),
+        spec: LaunchSpec {
+            env: vars,
+            port: service.port,
+            shell: project.shell,
+            pty: project.pty,
+            restart: project.restart,
+            max_restarts: project.max_restarts,
+            stop_timeout: project.stop_timeout,
+            health: service.health.clone(),
+            ..LaunchSpec::new(&cwd, &service.command)
+        },
+    }
+}
+
+/// The project's environment, with its `.venv` activated when it has one, so
+/// `python` and the tools installed into it are the virtualenv's.
+fn launch_env(project: &ProjectEntry) -> Vec<(String, String)> {
+    let mut vars = project.process_env();
+    if let Some(bin) = deps::venv_bin(Path::new(&project.path)) {
+        env::activate_venv(&mut vars, &bin);
+    }
+    vars
+}
+
+/// Everything a run starts for a project: its dev server (if any), then each service.
+pub fn all(project: &ProjectEntry, detection: Option<&DetectedProject>, store: &ProjectStore) -> Vec<Target> {
+    dev_server(project, detection, store)
+        .into_iter()
+        .chain(project.services.iter().map(|s| service(project, s)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detect::{PortStyle, ProjectType};
+    use crate::store::ServiceEntry;
+
+    #[test]
+    fn test_services_replace_detected_command() {
+        let detection = DetectedProject {
+            package_manager: None,
+            run_command: Some("cargo run".to_string()),
+            project_type: ProjectType::Rust,
+            packages: Vec::new(),
+            port_style: Some(PortStyle::Env("ROCKET_PORT")),
+        };
+        let mut project = ProjectEntry {
+            name: "shop".to_string(),
+            path: "/home/user/shop".to_string(),
+            ..Default::default()
+        };
+
+        // Without services the detected command runs as the project's dev server
+        let targets = all(&project, Some(&detection), &ProjectStore::default());
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].key, "shop");
+        assert_eq!(targets[0].spec.command, "cargo run");
+        let port = targets[0].spec.port.unwrap().to_string();
+        assert!(targets[0].spec.env.contains(&("ROCKET_PORT".to_string(), port)));
+
+        project.services.push(ServiceEntry {
+            name: "api".to_string(),
+            command: "cargo run -p api".to_string(),
+            cwd: Some("crates/api".to_string()),
+            port: Some(8080),
+            ..Default::default()
+        });
+
+        // With services, only the services run
+        let targets = all(&project, Some(&detection), &ProjectStore::default());
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].key, "shop/api");
+        assert_eq!(targets[0].spec.port, Some(8080));
+        assert_eq!(
+            targets[0].spec.cwd,
+            Path::new("/home/user/shop/crates/api")
+        );
+    }
+
+    #[test]
+    fn test_force_color_env() {
+        let mut project = ProjectEntry {
+            name: "shop".to_string(),
+            path: "/home/user/shop".to_string(),
+            run_command: Some("npm run dev".to_string()),
+            force_color: true,
+            ..Default::default()
+        };
+        project.env.insert("CLICOLOR_FORCE".to_string(), "0".to_string());
+        project.services.push(ServiceEntry {
+            name: "worker".to_string(),
+            command: "node worker.js".to_string(),
+            env: [("FORCE_COLOR".to_string(), "3".to_string())].into(),
+            ..Default::default()
+        });
+
+        let targets = all(&project, None, &ProjectStore::default());
+        let value = |t: &Target, key: &str| {
+            t.spec.env.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+        };
+        // Variables the project sets itself are left alone
+        assert_eq!(value(&targets[0], "FORCE_COLOR").as_deref(), Some("1"));
+        assert_eq!(value(&targets[0], "CLICOLOR_FORCE").as_deref(), Some("0"));
+        assert_eq!(value(&targets[1], "FORCE_COLOR").as_deref(), Some("3"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_venv_python_comes_first() {
+        use crate::process::ProcessManager;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("cm-venv-{}", std::process::id()));
+        let bin = dir.join(".venv").join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let python = bin.join("python");
+        std::fs::write(&python, "#!/bin/sh\necho \"venv python $VIRTUAL_ENV\"\n").unwrap();
+        std::fs::set_permissions(&python, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let project = ProjectEntry {
+            name: "api".to_string(),
+            path: dir.to_string_lossy().to_string(),
+            run_command: Some("python manage.py runserver".to_string()),
+            ..Default::default()
+        };
+        let target = dev_server(&project, None, &ProjectStore::default()).unwrap();
+
+        let mut pm = ProcessManager::new();
+        pm.launch(&target.key, &target.spec).expect("Failed to start process");
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let output = pm.get_output(&target.key);
+        let expected = format!("venv python {}", dir.join(".venv").display());
+        assert!(output.iter().any(|l| l.contains(&expected)), "got: {:?}", output);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
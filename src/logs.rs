@@ -0,0 +1,240 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::process::unix_now;
+
+/// Log file settings, the `[logs]` table in `projects.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    /// Write every line of process output to a log file
+    pub enabled: bool,
+    /// A log file growing past this continues in a new part (`<run>.1.log`, ...)
+    pub max_file_mb: u64,
+    /// Logs older than this are deleted when a new run starts
+    pub max_age_days: u64,
+    /// Beyond this total, a process's oldest logs are deleted
+    pub max_total_mb: u64,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_file_mb: 10,
+            max_age_days: 7,
+            max_total_mb: 100,
+        }
+    }
+}
+
+impl LogSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A log file on disk, as listed for a process.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// Returns the path to `~/.claude-manager/logs`.
+pub fn logs_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude-manager").join("logs"))
+}
+
+/// Directory holding a process's logs: `logs/<project>`, or
+/// `logs/<project>/<service>` for a service.
+pub fn dir_for(key: &str) -> Option<PathBuf> {
+    logs_dir().map(|dir| dir.join(key))
+}
+
+/// Log files of a process, newest first.
+pub fn list(key: &str) -> Vec<LogEntry> {
+    dir_for(key).map(|dir| list_dir(&dir)).unwrap_or_default()
+}
+
+fn list_dir(dir: &Path) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "log"))
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some(LogEntry {
+                path: e.path(),
+                size: meta.len(),
+                modified: meta.modified().ok()?,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| b.modified.cmp(&a.modified).then(b.path.cmp(&a.path)));
+    entries
+}
+
+/// Delete logs older than `max_age_days`, then the oldest ones until the
+/// directory fits in `max_total_mb`.
+fn prune(dir: &Path, settings: &LogSettings) {
+    let max_age = Duration::from_secs(settings.max_age_days * 24 * 60 * 60);
+    let mut budget = settings.max_total_mb * 1024 * 1024;
+
+    for entry in list_dir(dir) {
+        let age = entry.modified.elapsed().unwrap_or_default();
+        if age > max_age || entry.size > budget {
+            let _ = fs::remove_file(&entry.path);
+            budget = 0;
+        } else {
+            budget -= entry.size;
+        }
+    }
+}
+
+/// Format a Unix timestamp as `2024-05-01_14-03-59` (UTC), for file names.
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// The log of one run. Rolls over to a new part file when it grows past
+/// `max_file_mb`.
+pub struct LogFile {
+    /// First part, named after the run's start time
+    path: PathBuf,
+    file: File,
+    written: u64,
+    part: u32,
+    max_bytes: u64,
+}
+
+impl LogFile {
+    /// Start a new log for a run of `key`, pruning its old logs first.
+    pub fn create(key: &str, settings: &LogSettings) -> Result<Self> {
+        let dir = dir_for(key).ok_or_else(|| anyhow::anyhow!("Cannot find home directory"))?;
+        fs::create_dir_all(&dir)?;
+        prune(&dir, settings);
+
+        // Two runs can start within the same second
+        let stamp = format_timestamp(unix_now());
+        let mut path = dir.join(format!("{}.log", stamp));
+        let mut n = 1;
+        while path.exists() {
+            n += 1;
+            path = dir.join(format!("{}-{}.log", stamp, n));
+        }
+
+        let file = OpenOptions::new().create_new(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file,
+            written: 0,
+            part: 0,
+            max_bytes: settings.max_file_mb.max(1) * 1024 * 1024,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write_line(&mut self, line: &str) {
+        if self.written >= self.max_bytes {
+            self.part += 1;
+            let next = self.path.with_extension(format!("{}.log", self.part));
+            match OpenOptions::new().create(true).append(true).open(next) {
+                Ok(file) => {
+                    self.file = file;
+                    self.written = 0;
+                }
+                // Keep writing past the limit rather than lose output
+                Err(_) => self.max_bytes = u64::MAX,
+            }
+        }
+        if writeln!(self.file, "{}", line).is_ok() {
+            self.written += line.len() as u64 + 1;
+        }
+    }
+}
+
+/// The last `max_lines` lines of a log file.
+pub fn read_tail(path: &Path, max_lines: usize) -> Result<Vec<String>> {
+    let content = fs::read(path)?;
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    Ok(lines[start..].iter().map(|l| l.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01_00-00-00");
+        assert_eq!(format_timestamp(951_827_696), "2000-02-29_12-34-56");
+        assert_eq!(format_timestamp(1_714_572_239), "2024-05-01_14-03-59");
+    }
+
+    #[test]
+    fn test_rollover_and_prune() {
+        let dir = std::env::temp_dir().join("claude-manager-logs-test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("run.log");
+        let mut log = LogFile {
+            path: path.clone(),
+            file: File::create(&path).unwrap(),
+            written: 0,
+            part: 0,
+            max_bytes: 10,
+        };
+        log.write_line("first line");
+        log.write_line("second line");
+        log.write_line("third line");
+
+        assert_eq!(read_tail(&path, 10).unwrap(), vec!["first line"]);
+        assert_eq!(read_tail(&dir.join("run.1.log"), 10).unwrap(), vec!["second line"]);
+        assert_eq!(read_tail(&dir.join("run.2.log"), 10).unwrap(), vec!["third line"]);
+
+        // A budget of zero keeps nothing
+        let settings = LogSettings {
+            max_total_mb: 0,
+            ..Default::default()
+        };
+        prune(&dir, &settings);
+        assert!(list_dir(&dir).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,22 +1,45 @@
 mod app;
+mod cli;
+mod deps;
 mod detect;
+mod env;
 mod gh;
 mod git_status;
 mod git_worker;
+mod health;
+mod launch;
+mod logs;
+mod pipeline;
 mod theme;
 mod ports;
 mod process;
+#[cfg(unix)]
+mod proc_tree;
+#[cfg(unix)]
+mod pty;
+mod report;
 mod scanner;
+#[cfg(not(windows))]
+mod shell;
+mod signals;
 mod store;
+mod supervisor;
 mod tui;
 mod ui;
 mod updater;
+mod workspace;
 
 use crate::store::{ProjectEntry, ProjectStore};
 
 fn main() -> anyhow::Result<()> {
     updater::cleanup_old_exe();
 
+    // Any arguments select a headless subcommand instead of the dashboard
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&args);
+    }
+
     let mut store = ProjectStore::load()?;
     let first_run = store.is_first_run();
 
@@ -85,6 +108,7 @@ fn run_first_time_setup(store: &mut ProjectStore) -> anyhow::Result<()> {
                     repo_url: proj.remote_url.clone(),
                     path: proj.path.to_string_lossy().to_string(),
                     run_command: None,
+                    ..Default::default()
                 });
                 println!("  Imported: {}", proj.name);
             }
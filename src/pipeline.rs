@@ -0,0 +1,665 @@
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::deps::{self, InstallRecord, InstallState};
+use crate::detect::DetectedProject;
+use crate::process::{describe_exit, exit_signal};
+#[cfg(not(windows))]
+use crate::shell;
+use crate::store::{Hook, OnFailure, ProjectEntry};
+
+/// Output lines kept from the pipeline's commands.
+const MAX_OUTPUT_LINES: usize = 500;
+
+/// Seconds a hook may run before it is killed, unless it sets `timeout`.
+pub const DEFAULT_HOOK_TIMEOUT: u64 = 600;
+
+/// How often a running command is checked for exit or timeout.
+const POLL: Duration = Duration::from_millis(50);
+
+/// How long to wait, once a command has exited, for the rest of its output.
+/// Something it left running in the background can hold its pipes open for
+/// good.
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// What a step of a pipeline does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Run a command in the project directory, in the background
+    Command(CommandSpec),
+    /// Open a terminal with Claude; done by the app
+    OpenTerminal,
+    /// Start the dev server and services; done by the app
+    StartProcesses,
+    /// Start one of the project's services; done by the app
+    StartService(String),
+}
+
+/// A command line run by a pipeline step, the way the project runs its
+/// processes: parsed or through the login shell, with the project's env.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub command: String,
+    pub shell: bool,
+    pub env: Vec<(String, String)>,
+    pub timeout: Duration,
+    /// For the dependency install: where to save how it went
+    pub record: Option<InstallRecord>,
+}
+
+impl CommandSpec {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            shell: false,
+            env: Vec::new(),
+            timeout: Duration::from_secs(DEFAULT_HOOK_TIMEOUT),
+            record: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepState {
+    Pending,
+    Running,
+    Done,
+    Failed(String),
+    /// Not needed this time, and why
+    Skipped(String),
+}
+
+/// One step of a pipeline: `git fetch`, `pnpm install`, opening the terminal...
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub name: String,
+    pub action: Action,
+    /// Whether a failure stops the pipeline; optional steps are only reported
+    pub required: bool,
+    pub state: StepState,
+    started_at: Option<Instant>,
+    /// How long the step took once it is done or failed
+    pub took: Option<Duration>,
+}
+
+impl Step {
+    pub fn command(name: &str, spec: CommandSpec, required: bool) -> Self {
+        Self::new(name, Action::Command(spec), required)
+    }
+
+    pub fn new(name: &str, action: Action, required: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            action,
+            required,
+            state: StepState::Pending,
+            started_at: None,
+            took: None,
+        }
+    }
+
+    /// Mark the step as not needed this time; it is still listed, so the
+    /// run shows why.
+    pub fn skip(mut self, reason: &str) -> Self {
+        self.state = StepState::Skipped(reason.to_string());
+        self
+    }
+
+    /// Time spent so far, or in total once finished.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.took.or_else(|| self.started_at.map(|t| t.elapsed()))
+    }
+}
+
+/// What a pipeline is doing to its project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// `r`: pre-run hooks, the terminal, the processes, post-start hooks
+    Run,
+    /// The post-stop hooks, after the project was stopped
+    Stop,
+}
+
+impl Kind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Kind::Run => "Run",
+            Kind::Stop => "Stop",
+        }
+    }
+}
+
+/// The steps `r` takes for a project: its pre-run hooks (by default the
+/// built-in fetch and install), opening Claude, starting the processes, then
+/// its post-start hooks.
+pub fn run_steps(project: &ProjectEntry, detection: Option<&DetectedProject>) -> Vec<Step> {
+    let start = vec![
+        Step::new("Claude terminal", Action::OpenTerminal, false),
+        Step::new("start dev server", Action::StartProcesses, true),
+    ];
+    with_hooks(project, detection, start)
+}
+
+/// The steps `r` takes on a service row: the same hooks around starting just
+/// that service.
+pub fn service_run_steps(
+    project: &ProjectEntry,
+    service: &str,
+    detection: Option<&DetectedProject>,
+) -> Vec<Step> {
+    let start = Step::new(&format!("start {}", service), Action::StartService(service.to_string()), true);
+    with_hooks(project, detection, vec![start])
+}
+
+/// `start` between the project's pre-run and post-start hooks.
+fn with_hooks(project: &ProjectEntry, detection: Option<&DetectedProject>, start: Vec<Step>) -> Vec<Step> {
+    let env = project.process_env();
+    let mut steps: Vec<Step> = project
+        .pre_run_hooks()
+        .iter()
+        .filter_map(|hook| hook_step(project, hook, detection, &env))
+        .collect();
+    steps.extend(start);
+    steps.extend(
+        project
+            .post_start
+            .iter()
+            .filter_map(|hook| hook_step(project, hook, detection, &env)),
+    );
+    steps
+}
+
+/// The steps run after a project is stopped: its post-stop hooks.
+pub fn stop_steps(project: &ProjectEntry) -> Vec<Step> {
+    let env = project.process_env();
+    project
+        .post_stop
+        .iter()
+        .filter_map(|hook| hook_step(project, hook, None, &env))
+        .collect()
+}
+
+/// The step for one hook. Built-in hooks that don't apply to the project,
+/// such as `@fetch` outside a git repository, give none.
+fn hook_step(
+    project: &ProjectEntry,
+    hook: &Hook,
+    detection: Option<&DetectedProject>,
+    env: &[(String, String)],
+) -> Option<Step> {
+    let path = Path::new(&project.path);
+    let mut record = None;
+    let (name, command, on_failure) = match hook.command.as_str() {
+        Hook::FETCH => {
+            if !path.join(".git").exists() {
+                return None;
+            }
+            // Being offline shouldn't keep anyone from working
+            ("git fetch".to_string(), "git fetch --all --prune".to_string(), OnFailure::Continue)
+        }
+        Hook::INSTALL => {
+            let installer = deps::installer(path, detection)?;
+            if installer.is_up_to_date(path, InstallState::load(&project.name).as_ref()) {
+                let step = Step::command(&installer.name, CommandSpec::new(&installer.command), true);
+                return Some(step.skip("dependencies up to date"));
+            }
+            let (name, command) = (installer.name.clone(), installer.command.clone());
+            record = Some(InstallRecord {
+                project: project.name.clone(),
+                path: path.to_path_buf(),
+                installer,
+            });
+            (name, command, OnFailure::Abort)
+        }
+        command => (command.to_string(), command.to_string(), OnFailure::Abort),
+    };
+
+    let spec = CommandSpec {
+        shell: project.shell,
+        env: env.to_vec(),
+        timeout: Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT)),
+        record,
+        ..CommandSpec::new(&command)
+    };
+    let required = hook.on_failure.unwrap_or(on_failure) == OnFailure::Abort;
+    Some(Step::command(hook.name.as_deref().unwrap_or(&name), spec, required))
+}
+
+enum Event {
+    Output(String),
+    Finished(usize, Result<(), String>),
+}
+
+/// A sequence of steps run in order, with command steps executed on a
+/// background thread so the UI keeps drawing. Drive it from the render loop
+/// with `advance`.
+pub struct Pipeline {
+    pub kind: Kind,
+    pub steps: Vec<Step>,
+    /// Combined output of the command steps
+    pub output: Vec<String>,
+    cwd: PathBuf,
+    tx: Sender<Event>,
+    rx: Receiver<Event>,
+    /// Set by `cancel`; the running command is killed when it sees it
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Pipeline {
+    /// A pipeline running `steps` in `cwd`; nothing starts until `advance`.
+    pub fn new(kind: Kind, cwd: &Path, steps: Vec<Step>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            kind,
+            steps,
+            output: Vec::new(),
+            cwd: cwd.to_path_buf(),
+            tx,
+            rx,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Give up on the remaining steps, killing the command in progress, such
+    /// as when the project is stopped before its run got to starting it.
+    pub fn cancel(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        for step in &mut self.steps {
+            if matches!(step.state, StepState::Pending | StepState::Running) {
+                step.took = step.started_at.map(|t| t.elapsed());
+                step.state = StepState::Skipped("cancelled".to_string());
+            }
+        }
+    }
+
+    /// Apply progress reported by the background thread.
+    pub fn poll(&mut self) {
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                Event::Output(line) => self.push_output(line),
+                Event::Finished(_, _) if self.cancelled.load(Ordering::SeqCst) => {}
+                Event::Finished(i, result) => self.finish(i, result),
+            }
+        }
+    }
+
+    /// Pick up progress and move on to the next step once the current one is
+    /// done. Commands are started in the background; a step the app has to
+    /// perform itself is returned, marked as running, for the app to `finish`.
+    pub fn advance(&mut self) -> Option<(usize, Action)> {
+        self.poll();
+        if self.failure().is_some() || self.current().is_some() {
+            return None;
+        }
+        let i = self.steps.iter().position(|s| s.state == StepState::Pending)?;
+        self.begin(i);
+        match self.steps[i].action.clone() {
+            Action::Command(spec) => {
+                let (cwd, tx) = (self.cwd.clone(), self.tx.clone());
+                let cancelled = Arc::clone(&self.cancelled);
+                thread::spawn(move || {
+                    let result = run_command(&cwd, &spec, &tx, &cancelled);
+                    if let Some(ref record) = spec.record {
+                        let _ = record.save(result.is_ok());
+                    }
+                    let _ = tx.send(Event::Finished(i, result));
+                });
+                None
+            }
+            action => Some((i, action)),
+        }
+    }
+
+    pub fn begin(&mut self, i: usize) {
+        if let Some(step) = self.steps.get_mut(i) {
+            step.state = StepState::Running;
+            step.started_at = Some(Instant::now());
+        }
+    }
+
+    pub fn finish(&mut self, i: usize, result: Result<(), String>) {
+        if let Some(step) = self.steps.get_mut(i) {
+            step.took = step.started_at.map(|t| t.elapsed());
+            step.state = match result {
+                Ok(()) => StepState::Done,
+                Err(e) => StepState::Failed(e),
+            };
+        }
+    }
+
+    fn push_output(&mut self, line: String) {
+        self.output.push(line);
+        if self.output.len() > MAX_OUTPUT_LINES {
+            self.output.remove(0);
+        }
+    }
+
+    /// The required step that failed and stopped the pipeline.
+    pub fn failure(&self) -> Option<&Step> {
+        self.steps
+            .iter()
+            .find(|s| s.required && matches!(s.state, StepState::Failed(_)))
+    }
+
+    /// Optional steps that failed without stopping the pipeline.
+    pub fn warnings(&self) -> impl Iterator<Item = &Step> {
+        self.steps
+            .iter()
+            .filter(|s| !s.required && matches!(s.state, StepState::Failed(_)))
+    }
+
+    /// True until every step has finished or one has stopped the pipeline.
+    pub fn is_running(&self) -> bool {
+        self.failure().is_none()
+            && self
+                .steps
+                .iter()
+                .any(|s| matches!(s.state, StepState::Pending | StepState::Running))
+    }
+
+    /// The step in progress, if any.
+    pub fn current(&self) -> Option<&Step> {
+        self.steps.iter().find(|s| s.state == StepState::Running)
+    }
+}
+
+/// Run one command to completion, streaming its stdout and stderr as output
+/// events. Fails with the exit status when it doesn't succeed, and kills it
+/// with everything it started when it outlives its timeout or `cancelled` is
+/// set.
+fn run_command(cwd: &Path, spec: &CommandSpec, tx: &Sender<Event>, cancelled: &AtomicBool) -> Result<(), String> {
+    // On Windows, npm/pnpm/yarn/bun are .cmd files, must run through cmd.exe
+    #[cfg(windows)]
+    let mut cmd = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        let mut cmd = Command::new("cmd.exe");
+        cmd.args(["/c", &spec.command])
+            .creation_flags(CREATE_NO_WINDOW)
+            .envs(spec.env.iter().map(|(k, v)| (k, v)));
+        cmd
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        use std::os::unix::process::CommandExt;
+        let invocation = match spec.shell {
+            true => shell::login_shell(&spec.command),
+            false => shell::parse(&spec.command).map_err(|e| e.to_string())?,
+        };
+        let mut cmd = Command::new(&invocation.program);
+        cmd.args(&invocation.args)
+            .envs(spec.env.iter().map(|(k, v)| (k, v)))
+            .envs(invocation.env)
+            // Its own group, so a timeout reaches everything it started
+            .process_group(0);
+        cmd
+    };
+
+    let mut child = cmd
+        .current_dir(cwd)
+        // Never stop to ask for credentials: there is no one to answer
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot run {}: {}", spec.command, e))?;
+
+    let forward = |stream: Box<dyn Read + Send>, tx: Sender<Event>| {
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                let _ = tx.send(Event::Output(line));
+            }
+        })
+    };
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward(Box::new(stdout), tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward(Box::new(stderr), tx.clone()));
+    }
+
+    let deadline = Instant::now() + spec.timeout;
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                kill(&mut child);
+                return Err(format!("timed out after {}s", spec.timeout.as_secs()));
+            }
+            None if cancelled.load(Ordering::SeqCst) => {
+                kill(&mut child);
+                return Err("cancelled".to_string());
+            }
+            None => thread::sleep(POLL),
+        }
+    };
+    let drained = Instant::now() + OUTPUT_GRACE;
+    while readers.iter().any(|r| !r.is_finished()) && Instant::now() < drained {
+        thread::sleep(POLL);
+    }
+    if status.success() {
+        Ok(())
+    } else {
+        Err(describe_exit(status.code(), exit_signal(&status)))
+    }
+}
+
+/// Kill a command that ran out of time, along with its process tree.
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let pid = child.id();
+        crate::proc_tree::signal(pid, &crate::proc_tree::descendants(pid), libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detect;
+
+    /// Drive the pipeline to the end, performing app steps as they come up.
+    fn run(pipeline: &mut Pipeline) -> Vec<Action> {
+        let mut performed = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while pipeline.is_running() && Instant::now() < deadline {
+            match pipeline.advance() {
+                Some((i, action)) => {
+                    performed.push(action);
+                    pipeline.finish(i, Ok(()));
+                }
+                None => thread::sleep(Duration::from_millis(10)),
+            }
+        }
+        pipeline.poll();
+        performed
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_runs_steps_in_order() {
+        let mut pipeline = Pipeline::new(
+            Kind::Run,
+            &std::env::temp_dir(),
+            vec![
+                Step::command("fetch", CommandSpec::new("sh -c 'echo fetching; exit 1'"), false),
+                Step::command("install", CommandSpec::new("echo installed"), true),
+                Step::new("start", Action::StartProcesses, true),
+                Step::command("seed", CommandSpec::new("sh -c 'echo seeded'"), true),
+            ],
+        );
+        let performed = run(&mut pipeline);
+
+        // An optional failure is reported, and the run carries on
+        assert_eq!(pipeline.steps[0].state, StepState::Failed("exit code 1".to_string()));
+        assert_eq!(pipeline.warnings().count(), 1);
+        assert_eq!(performed, vec![Action::StartProcesses]);
+        // The post-start command ran after the app step
+        assert_eq!(pipeline.steps[3].state, StepState::Done);
+        assert_eq!(pipeline.output, vec!["fetching", "installed", "seeded"]);
+        assert!(pipeline.advance().is_none());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_required_failure_stops_the_run() {
+        let mut pipeline = Pipeline::new(
+            Kind::Run,
+            &std::env::temp_dir(),
+            vec![
+                Step::command("install", CommandSpec::new("sh -c 'exit 3'"), true),
+                Step::command("build", CommandSpec::new("echo never"), true),
+                Step::new("start", Action::StartProcesses, true),
+            ],
+        );
+        let performed = run(&mut pipeline);
+
+        assert_eq!(pipeline.failure().unwrap().name, "install");
+        assert_eq!(pipeline.steps[1].state, StepState::Pending);
+        assert!(pipeline.output.is_empty());
+        assert!(performed.is_empty());
+        assert!(!pipeline.is_running());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_hook_timeout_kills_it() {
+        let spec = CommandSpec {
+            timeout: Duration::from_secs(1),
+            ..CommandSpec::new("sh -c 'echo started; sleep 30'")
+        };
+        let mut pipeline = Pipeline::new(
+            Kind::Stop,
+            &std::env::temp_dir(),
+            vec![Step::command("slow", spec, true)],
+        );
+        let started = Instant::now();
+        run(&mut pipeline);
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            pipeline.steps[0].state,
+            StepState::Failed("timed out after 1s".to_string())
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_background_process_does_not_hold_up_the_step() {
+        // The sleep inherits the hook's stdout and keeps it open
+        let mut pipeline = Pipeline::new(
+            Kind::Run,
+            &std::env::temp_dir(),
+            vec![Step::command("watch", CommandSpec::new("sh -c 'sleep 10 & echo started'"), true)],
+        );
+        let started = Instant::now();
+        run(&mut pipeline);
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(pipeline.steps[0].state, StepState::Done);
+        assert_eq!(pipeline.output, vec!["started"]);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_cancel_skips_the_rest() {
+        let mut pipeline = Pipeline::new(
+            Kind::Run,
+            &std::env::temp_dir(),
+            vec![
+                Step::command("install", CommandSpec::new("sh -c 'echo installing; sleep 30'"), true),
+                Step::new("start", Action::StartProcesses, true),
+            ],
+        );
+        assert!(pipeline.advance().is_none());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pipeline.output.is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+            pipeline.poll();
+        }
+
+        pipeline.cancel();
+        let performed = run(&mut pipeline);
+        assert!(performed.is_empty(), "the processes were not started");
+        assert!(!pipeline.is_running());
+        assert!(pipeline.failure().is_none());
+        assert_eq!(pipeline.steps[1].state, StepState::Skipped("cancelled".to_string()));
+    }
+
+    #[test]
+    fn test_hooks_become_steps() {
+        let dir = std::env::temp_dir().join(format!("cm-hooks-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        let detection = DetectedProject {
+            package_manager: Some(detect::PackageManager::Pnpm),
+            run_command: Some("pnpm dev".to_string()),
+            project_type: detect::ProjectType::JavaScript,
+            packages: Vec::new(),
+            port_style: None,
+        };
+        let mut project = ProjectEntry {
+            name: "shop".to_string(),
+            path: dir.to_string_lossy().to_string(),
+            post_start: vec![Hook::new("pnpm seed")],
+            ..Default::default()
+        };
+
+        let names = |steps: &[Step]| steps.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+        let steps = run_steps(&project, Some(&detection));
+        assert_eq!(
+            names(&steps),
+            vec!["git fetch", "pnpm install", "Claude terminal", "start dev server", "pnpm seed"]
+        );
+        assert!(!steps[0].required, "fetch failures only warn");
+        assert!(steps[1].required);
+
+        // Configured hooks replace the built-in ones, which may be kept by name
+        project.pre_run = Some(vec![
+            Hook {
+                timeout: Some(30),
+                on_failure: Some(OnFailure::Continue),
+                ..Hook::new("docker compose up -d")
+            },
+            Hook::new(Hook::INSTALL),
+        ]);
+        let steps = run_steps(&project, None);
+        assert_eq!(
+            names(&steps),
+            vec!["docker compose up -d", "Claude terminal", "start dev server", "pnpm seed"]
+        );
+        assert!(!steps[0].required);
+        match steps[0].action {
+            Action::Command(ref spec) => assert_eq!(spec.timeout, Duration::from_secs(30)),
+            _ => panic!("expected a command"),
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_service_run_keeps_the_hooks() {
+        let project = ProjectEntry {
+            name: "shop".to_string(),
+            path: std::env::temp_dir().to_string_lossy().to_string(),
+            pre_run: Some(vec![Hook::new("docker compose up -d")]),
+            post_start: vec![Hook::new("pnpm seed")],
+            ..Default::default()
+        };
+
+        let steps = service_run_steps(&project, "worker", None);
+        let names: Vec<&str> = steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["docker compose up -d", "start worker", "pnpm seed"]);
+        assert_eq!(steps[1].action, Action::StartService("worker".to_string()));
+    }
+}
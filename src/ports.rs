@@ -1,5 +1,10 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::net::TcpStream;
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
 use std::time::Duration;
 
 #[derive(Debug, Clone)]
@@ -9,111 +14,442 @@ pub struct PortInfo {
     pub process_name: Option<String>,
 }
 
-/// Common dev server ports to scan
-const DEV_PORTS: &[u16] = &[
-    3000, 3001, 3002, 3003, 3004, 3005, 3006, 3007, 3008, 3009, 3010,
-    4000, 4001, 4002, 4003, 4004, 4005, 4006, 4007, 4008, 4009, 4010,
-    5000, 5001, 5002, 5003, 5004, 5005, 5006, 5007, 5008, 5009, 5010,
-    5173, 5174, 5175, // Vite default ports
-    8000, 8001, 8002, 8003, 8004, 8005, 8006, 8007, 8008, 8009, 8010,
-    8080, 9000,
-];
+impl PortInfo {
+    /// The process holding the port, e.g. "node (pid 4120)".
+    pub fn describe(&self) -> String {
+        match (&self.process_name, self.pid) {
+            (Some(name), Some(pid)) => format!("{} (pid {})", name, pid),
+            (None, Some(pid)) => format!("pid {}", pid),
+            _ => "another process".to_string(),
+        }
+    }
+}
+
+/// Most ports the ranges may cover together. Every port lookup walks them,
+/// and off Linux the ports bar connects to each one.
+pub const MAX_PORTS: usize = 1000;
+
+/// Connections the ports bar makes at once off Linux.
+#[cfg(not(target_os = "linux"))]
+const SCAN_THREADS: usize = 32;
 
-/// Find the next available port from the common dev server ports
-pub fn find_available_port() -> Option<u16> {
-    DEV_PORTS.iter().find(|&&port| !is_port_open(port)).copied()
+/// Port settings, the `[ports]` table in `projects.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortSettings {
+    /// Where dev servers get their ports from, and what the ports bar always
+    /// watches: single ports such as "8080" or ranges such as "3000-3010"
+    pub ranges: Vec<String>,
 }
 
-pub fn scan_ports() -> Vec<PortInfo> {
-    let ports_to_check: Vec<u16> = DEV_PORTS.to_vec();
+impl Default for PortSettings {
+    fn default() -> Self {
+        let ranges = ["3000-3010", "4000-4010", "5000-5010", "5173-5175", "8000-8010", "8080", "9000"];
+        Self {
+            ranges: ranges.map(String::from).to_vec(),
+        }
+    }
+}
 
-    let mut results = Vec::new();
+impl PortSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 
-    for port in ports_to_check {
-        if is_port_open(port) {
-            let (pid, name) = get_process_for_port(port).unwrap_or((None, None));
-            results.push(PortInfo {
-                port,
-                pid,
-                process_name: name,
-            });
+    /// Check every range, so a typo is reported when the settings load
+    /// rather than quietly leaving ports out.
+    pub fn validate(&self) -> Result<()> {
+        let mut ports = BTreeSet::new();
+        for range in &self.ranges {
+            ports.extend(parse_range(range)?);
         }
+        if ports.len() > MAX_PORTS {
+            bail!(
+                "The port ranges cover {} ports; together they may cover at most {}",
+                ports.len(),
+                MAX_PORTS
+            );
+        }
+        Ok(())
+    }
+
+    /// Every port in the ranges, in order and without repeats, up to
+    /// `MAX_PORTS`. Invalid entries, already reported by `validate`, are
+    /// skipped.
+    pub fn ports(&self) -> Vec<u16> {
+        let mut seen = BTreeSet::new();
+        self.ranges
+            .iter()
+            .filter_map(|range| parse_range(range).ok())
+            .flatten()
+            .filter(|&port| seen.insert(port))
+            .take(MAX_PORTS)
+            .collect()
+    }
+
+    /// The first free port in the ranges that isn't in `taken`, such as
+    /// ports reserved by projects that aren't running.
+    pub fn find_available(&self, taken: &[u16]) -> Option<u16> {
+        let listening = all_listening_ports();
+        self.ports()
+            .into_iter()
+            .find(|&port| !taken.contains(&port) && !is_listening(port, listening.as_deref()))
     }
 
-    results
+    /// The port for a dev server: its preferred port while free, else the
+    /// first free one nobody has reserved. A pinned port is kept even when
+    /// taken; the caller deals with whatever holds it.
+    pub fn assign(&self, preferred: Option<u16>, pinned: bool, reserved: &[u16]) -> Option<u16> {
+        match preferred {
+            Some(port) if pinned || !is_port_open(port) => Some(port),
+            _ => self.find_available(reserved),
+        }
+    }
 }
 
-fn is_port_open(port: u16) -> bool {
-    TcpStream::connect_timeout(
-        &format!("127.0.0.1:{}", port).parse().unwrap(),
-        Duration::from_millis(50),
-    )
-    .is_ok()
+/// A single port such as "8080", or a range such as "3000-3010".
+fn parse_range(range: &str) -> Result<RangeInclusive<u16>> {
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let (start, end) = match (start.trim().parse::<u16>(), end.trim().parse::<u16>()) {
+        (Ok(start), Ok(end)) if start > 0 => (start, end),
+        _ => bail!("Port range '{}' is not a port or a range like 3000-3010", range),
+    };
+    if start > end {
+        bail!("Port range '{}' ends before it starts", range);
+    }
+    Ok(start..=end)
 }
 
-#[cfg(target_os = "linux")]
-fn get_process_for_port(port: u16) -> Result<(Option<u32>, Option<String>)> {
-    use std::fs;
-    use std::path::Path;
+/// Whether something listens on `port`: looked up in `listening` when the
+/// system could list its sockets, else by connecting.
+fn is_listening(port: u16, listening: Option<&[u16]>) -> bool {
+    match listening {
+        Some(listening) => listening.contains(&port),
+        None => is_port_open(port),
+    }
+}
 
-    // Read /proc/net/tcp to find the inode for this port
-    let tcp_content = fs::read_to_string("/proc/net/tcp")?;
-    let port_hex = format!("{:04X}", port);
+/// What is listening on `port`, if anything.
+pub fn holder(port: u16) -> Option<PortInfo> {
+    if !is_port_open(port) {
+        return None;
+    }
+    let (pid, process_name) = get_process_for_port(port).unwrap_or((None, None));
+    Some(PortInfo { port, pid, process_name })
+}
 
-    let mut target_inode: Option<u64> = None;
+/// Stop a process we didn't start, such as one holding a port, along with
+/// everything it started.
+pub fn kill(pid: u32) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::time::Instant;
 
-    for line in tcp_content.lines().skip(1) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 10 {
-            continue;
+        let mut tree = crate::proc_tree::descendants(pid);
+        tree.push(pid);
+        crate::proc_tree::signal(pid, &tree, libc::SIGTERM);
+        let alive = crate::proc_tree::wait_gone(&tree, Instant::now() + Duration::from_secs(3), || {});
+        if alive.is_empty() {
+            return Ok(());
+        }
+        crate::proc_tree::signal(pid, &alive, libc::SIGKILL);
+        let alive = crate::proc_tree::wait_gone(&alive, Instant::now() + Duration::from_secs(1), || {});
+        if !alive.is_empty() {
+            anyhow::bail!("pid {} is still running", pid);
         }
+        Ok(())
+    }
 
-        // local_address is like "00000000:0CEA" (ip:port in hex)
-        let local_addr = parts[1];
-        if let Some(local_port) = local_addr.split(':').nth(1) {
-            if local_port == port_hex {
-                // Found it, get inode (column 9)
-                if let Ok(inode) = parts[9].parse::<u64>() {
-                    target_inode = Some(inode);
-                    break;
-                }
-            }
+    #[cfg(windows)]
+    {
+        let status = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .output()?
+            .status;
+        if !status.success() {
+            anyhow::bail!("taskkill failed for pid {}", pid);
         }
+        Ok(())
     }
+}
 
-    let inode = match target_inode {
-        Some(i) => i,
-        None => return Ok((None, None)),
-    };
+/// Ports in use by dev servers: on Linux every listening socket outside the
+/// ephemeral range, plus any in the configured ranges; elsewhere the ports
+/// of the configured ranges that accept connections.
+pub fn scan_ports(settings: &PortSettings) -> Vec<PortInfo> {
+    let ranges: BTreeSet<u16> = settings.ports().into_iter().collect();
 
-    // Find which process owns this inode by checking /proc/*/fd/
-    for entry in fs::read_dir("/proc")? {
-        let entry = entry?;
-        let pid_str = entry.file_name().to_string_lossy().to_string();
-        let pid: u32 = match pid_str.parse() {
-            Ok(p) => p,
+    #[cfg(target_os = "linux")]
+    {
+        let ephemeral = ephemeral_ports();
+        let mut sockets: Vec<(u64, u16)> = listening_sockets()
+            .into_iter()
+            .filter(|&(_, port)| ranges.contains(&port) || (port >= 1024 && !ephemeral.contains(&port)))
+            .collect();
+        sockets.sort_by_key(|&(_, port)| port);
+        sockets.dedup_by_key(|&mut (_, port)| port);
+
+        let inodes: Vec<u64> = sockets.iter().map(|&(inode, _)| inode).collect();
+        let owners = socket_owners(&inodes);
+        sockets
+            .into_iter()
+            .map(|(inode, port)| {
+                let (pid, process_name) = match owners.get(&inode) {
+                    Some((pid, name)) => (Some(*pid), name.clone()),
+                    None => (None, None),
+                };
+                PortInfo { port, pid, process_name }
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        // Connect to a batch of ports at a time, so closed or filtered ones
+        // don't each add their timeout to the scan
+        let ranges: Vec<u16> = ranges.into_iter().collect();
+        let chunk = ranges.len().div_ceil(SCAN_THREADS).max(1);
+        let open: Vec<u16> = std::thread::scope(|scope| {
+            let workers: Vec<_> = ranges
+                .chunks(chunk)
+                .map(|ports| {
+                    scope.spawn(move || {
+                        ports.iter().copied().filter(|&port| is_port_open(port)).collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_default())
+                .collect()
+        });
+        open.into_iter()
+            .map(|port| {
+                let (pid, process_name) = get_process_for_port(port).unwrap_or((None, None));
+                PortInfo { port, pid, process_name }
+            })
+            .collect()
+    }
+}
+
+/// Every port something listens on, when the system can list them cheaply.
+fn all_listening_ports() -> Option<Vec<u16>> {
+    #[cfg(target_os = "linux")]
+    {
+        Some(listening_sockets().into_iter().map(|(_, port)| port).collect())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Ports the kernel hands out for outgoing connections and `bind(0)`;
+/// listeners there were not put there on purpose.
+#[cfg(target_os = "linux")]
+fn ephemeral_ports() -> std::ops::RangeInclusive<u16> {
+    let range = std::fs::read_to_string("/proc/sys/net/ipv4/ip_local_port_range").ok();
+    let mut bounds = range.iter().flat_map(|r| r.split_whitespace()).filter_map(|b| b.parse().ok());
+    match (bounds.next(), bounds.next()) {
+        (Some(start), Some(end)) => start..=end,
+        _ => 32768..=60999,
+    }
+}
+
+/// Which process owns each of `inodes`, found in one pass over `/proc/*/fd`.
+#[cfg(target_os = "linux")]
+fn socket_owners(inodes: &[u64]) -> std::collections::HashMap<u64, (u32, Option<String>)> {
+    use std::fs;
+
+    let mut owners = std::collections::HashMap::new();
+    if inodes.is_empty() {
+        return owners;
+    }
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return owners,
+    };
+    for pid in entries.flatten().filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok()) {
+        let fds = match fs::read_dir(format!("/proc/{}/fd", pid)) {
+            Ok(fds) => fds,
             Err(_) => continue,
         };
+        for fd in fds.flatten() {
+            let inode = fs::read_link(fd.path()).ok().and_then(|link| socket_inode(&link));
+            if let Some(inode) = inode.filter(|i| inodes.contains(i) && !owners.contains_key(i)) {
+                let name = fs::read_to_string(format!("/proc/{}/comm", pid))
+                    .ok()
+                    .map(|s| s.trim().to_string());
+                owners.insert(inode, (pid, name));
+            }
+        }
+    }
+    owners
+}
 
-        let fd_path = Path::new("/proc").join(&pid_str).join("fd");
-        if let Ok(fd_entries) = fs::read_dir(&fd_path) {
-            for fd_entry in fd_entries.flatten() {
-                if let Ok(link) = fs::read_link(fd_entry.path()) {
-                    let link_str = link.to_string_lossy();
-                    if link_str.contains(&format!("socket:[{}]", inode)) {
-                        // Found the process, get its name
-                        let comm_path = Path::new("/proc").join(&pid_str).join("comm");
-                        let name = fs::read_to_string(comm_path)
-                            .ok()
-                            .map(|s| s.trim().to_string());
-                        return Ok((Some(pid), name));
+/// The inode of a `/proc/<pid>/fd` link to a socket, `socket:[12345]`.
+#[cfg(target_os = "linux")]
+fn socket_inode(link: &std::path::Path) -> Option<u64> {
+    link.to_str()?.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+}
+
+fn is_port_open(port: u16) -> bool {
+    TcpStream::connect_timeout(
+        &format!("127.0.0.1:{}", port).parse().unwrap(),
+        Duration::from_millis(50),
+    )
+    .is_ok()
+}
+
+/// The port a line of dev server output says the server is on, as in
+/// "Local: http://localhost:5173/", "Listening on :8080" or "Tomcat started
+/// on port 8080".
+pub fn announced_port(line: &str) -> Option<u16> {
+    static PATTERNS: OnceLock<(Regex, Regex, Regex)> = OnceLock::new();
+    let (escapes, url, listening) = PATTERNS.get_or_init(|| {
+        (
+            Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").unwrap(),
+            Regex::new(r"https?://(?:\[[0-9a-fA-F:]*\]|[\w.-]+):(\d{2,5})\b").unwrap(),
+            // Four digits at least, so times like 12:30:45 aren't taken for ports
+            Regex::new(r"(?i)\b(?:listen|listening|running|started|serving|server)\b.*?(?:port(?:\(s\))?\s*:?\s*|:)(\d{4,5})\b")
+                .unwrap(),
+        )
+    });
+    let line = escapes.replace_all(line, "");
+    let captures = url.captures(&line).or_else(|| listening.captures(&line))?;
+    captures[1].parse().ok().filter(|&port| port != 0)
+}
+
+/// The port a server really serves on, given the ports its output announced,
+/// the one it was assigned and the ports its processes listen on, and whether
+/// it was seen listening there. Output can mention other servers' URLs, such
+/// as a proxy target, so an announced port only counts once it is seen
+/// listening. An unannounced port is only taken when it is the one socket
+/// listening: with several, the others may be an HMR websocket or a debugger.
+/// Otherwise the announcement, or else the assigned port, stands unconfirmed.
+pub fn actual_port(announced: &[u16], assigned: Option<u16>, listening: &[u16]) -> Option<(u16, bool)> {
+    let matched = announced
+        .iter()
+        .chain(assigned.iter())
+        .find(|port| listening.contains(port));
+    match (matched, listening) {
+        (Some(&port), _) => Some((port, true)),
+        (None, &[only]) => Some((only, true)),
+        (None, _) => announced.first().copied().or(assigned).map(|port| (port, false)),
+    }
+}
+
+/// TCP ports that any of `pids` is listening on, sorted.
+pub fn listening_ports(pids: &[u32]) -> Vec<u16> {
+    let mut ports = listening_ports_of(pids).unwrap_or_default();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// Listening TCP sockets from `/proc/net/tcp` and `tcp6`, as inode and port.
+#[cfg(target_os = "linux")]
+fn listening_sockets() -> Vec<(u64, u16)> {
+    const LISTEN: &str = "0A";
+    ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|table| std::fs::read_to_string(table).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .skip(1)
+                .filter_map(|line| {
+                    let parts: Vec<&str> = line.split_whitespace().collect();
+                    if parts.len() < 10 || parts[3] != LISTEN {
+                        return None;
                     }
-                }
+                    let port = u16::from_str_radix(parts[1].rsplit(':').next()?, 16).ok()?;
+                    Some((parts[9].parse().ok()?, port))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn listening_ports_of(pids: &[u32]) -> Result<Vec<u16>> {
+    use std::fs;
+
+    let sockets = listening_sockets();
+    let mut ports = Vec::new();
+    for pid in pids {
+        let fds = match fs::read_dir(format!("/proc/{}/fd", pid)) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+        for fd in fds.flatten() {
+            let link = match fs::read_link(fd.path()) {
+                Ok(link) => link,
+                Err(_) => continue,
+            };
+            if let Some(inode) = socket_inode(&link) {
+                ports.extend(sockets.iter().filter(|(i, _)| *i == inode).map(|(_, port)| *port));
             }
         }
     }
+    Ok(ports)
+}
 
-    Ok((None, None))
+#[cfg(target_os = "macos")]
+fn listening_ports_of(pids: &[u32]) -> Result<Vec<u16>> {
+    use std::process::Command;
+
+    if pids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pid_list: Vec<String> = pids.iter().map(|p| p.to_string()).collect();
+    let output = Command::new("lsof")
+        .args(["-a", "-p", &pid_list.join(","), "-iTCP", "-sTCP:LISTEN", "-n", "-P"])
+        .output()?;
+
+    // Name is the ninth column and ends with ":port"
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().nth(8)?.rsplit(':').next()?.parse().ok())
+        .collect())
+}
+
+#[cfg(target_os = "windows")]
+fn listening_ports_of(pids: &[u32]) -> Result<Vec<u16>> {
+    use std::process::Command;
+
+    let output = Command::new("netstat").args(["-ano"]).output()?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.contains("LISTENING"))
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let pid: u32 = parts.get(4)?.parse().ok()?;
+            pids.contains(&pid).then_some(())?;
+            parts.get(1)?.rsplit(':').next()?.parse().ok()
+        })
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn listening_ports_of(_pids: &[u32]) -> Result<Vec<u16>> {
+    Ok(Vec::new())
+}
+
+#[cfg(target_os = "linux")]
+fn get_process_for_port(port: u16) -> Result<(Option<u32>, Option<String>)> {
+    let inodes: Vec<u64> = listening_sockets()
+        .into_iter()
+        .filter(|&(_, p)| p == port)
+        .map(|(inode, _)| inode)
+        .collect();
+    let owners = socket_owners(&inodes);
+    Ok(inodes
+        .iter()
+        .find_map(|inode| owners.get(inode).cloned())
+        .map_or((None, None), |(pid, name)| (Some(pid), name)))
 }
 
 #[cfg(target_os = "macos")]
@@ -177,3 +513,106 @@ fn get_process_for_port(port: u16) -> Result<(Option<u32>, Option<String>)> {
 fn get_process_for_port(_port: u16) -> Result<(Option<u32>, Option<String>)> {
     Ok((None, None))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announced_port() {
+        let banners = [
+            ("  \x1b[32m➜\x1b[39m  \x1b[1mLocal\x1b[22m:   \x1b[36mhttp://localhost:\x1b[1m5174\x1b[22m/\x1b[39m", Some(5174)),
+            ("   - Local:        http://localhost:3000", Some(3000)),
+            ("Starting development server at http://127.0.0.1:8000/", Some(8000)),
+            ("Now listening on: http://[::]:5000", Some(5000)),
+            ("2024/05/01 12:30:45 Listening on :8080", Some(8080)),
+            ("Tomcat started on port(s): 8081 (http) with context path ''", Some(8081)),
+            ("[info] Running ShopWeb.Endpoint with Bandit 1.5.0 at 127.0.0.1:4000 (http)", Some(4000)),
+            ("Server started at 12:30:45", None),
+            ("compiled 42 modules in 1200ms", None),
+        ];
+        for (line, port) in banners {
+            assert_eq!(announced_port(line), port, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_actual_port() {
+        // Vite moved on from a taken port and said so
+        assert_eq!(actual_port(&[5174], Some(5173), &[5174, 24678]), Some((5174, true)));
+        // A proxy target in the output is not the server's own port
+        assert_eq!(actual_port(&[8080, 3000], Some(3000), &[3000]), Some((3000, true)));
+        // Ignored the assigned port without announcing one
+        assert_eq!(actual_port(&[], Some(3000), &[4200]), Some((4200, true)));
+        // Several other sockets, say an inspector and a websocket: keep the
+        // assigned port, unconfirmed
+        assert_eq!(actual_port(&[], Some(3000), &[9229, 24678]), Some((3000, false)));
+        assert_eq!(actual_port(&[8000], Some(3000), &[]), Some((8000, false)));
+        assert_eq!(actual_port(&[], None, &[]), None);
+    }
+
+    #[test]
+    fn test_assign_prefers_free_port() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Taken: a pinned port is kept, a preferred one given up
+        let defaults = PortSettings::default();
+        assert_eq!(defaults.assign(Some(port), true, &[]), Some(port));
+        assert_ne!(defaults.assign(Some(port), false, &[]), Some(port));
+        assert_eq!(holder(port).map(|h| h.port), Some(port));
+        let reserved = &defaults.ports()[..3];
+        assert!(!defaults.assign(None, false, reserved).is_some_and(|p| reserved.contains(&p)));
+
+        drop(listener);
+        assert_eq!(defaults.assign(Some(port), false, &[]), Some(port));
+    }
+
+    #[test]
+    fn test_port_ranges() {
+        let settings = PortSettings {
+            ranges: ["5173-5175", "8080", "nonsense", "5174"].map(String::from).to_vec(),
+        };
+        assert_eq!(settings.ports(), vec![5173, 5174, 5175, 8080]);
+        assert!(settings.validate().is_err());
+        // The same 49 ports as the list that came before the settings
+        assert_eq!(PortSettings::default().ports().len(), 49);
+        assert!(PortSettings::default().validate().is_ok());
+
+        for bad in ["9000-8000", "abc", "0"] {
+            let settings = PortSettings {
+                ranges: vec![bad.to_string()],
+            };
+            assert!(settings.validate().is_err(), "{} accepted", bad);
+            assert!(settings.ports().is_empty());
+        }
+
+        // The cap is on all the ranges together
+        let settings = PortSettings {
+            ranges: ["3000-3600", "8000-8600"].map(String::from).to_vec(),
+        };
+        assert!(settings.validate().is_err());
+        assert_eq!(settings.ports().len(), MAX_PORTS);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_scan_finds_ports_outside_ranges() {
+        let listener = (20000..20100)
+            .find_map(|port| std::net::TcpListener::bind(("127.0.0.1", port)).ok())
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let found = scan_ports(&PortSettings::default());
+        let info = found.iter().find(|p| p.port == port).expect("listener found");
+        assert_eq!(info.pid, Some(std::process::id()));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_listening_ports() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(listening_ports(&[std::process::id()]).contains(&port));
+        assert!(!listening_ports(&[]).contains(&port));
+    }
+}
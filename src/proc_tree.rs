@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often to check whether stopped processes are gone.
+const POLL: Duration = Duration::from_millis(50);
+
+/// Every process descended from `pid`, children first.
+pub fn descendants(pid: u32) -> Vec<u32> {
+    let parents = parent_map();
+    let mut found = Vec::new();
+    let mut frontier = vec![pid];
+    while let Some(parent) = frontier.pop() {
+        for (&child, _) in parents.iter().filter(|(_, &ppid)| ppid == parent) {
+            if !found.contains(&child) {
+                found.push(child);
+                frontier.push(child);
+            }
+        }
+    }
+    found
+}
+
+/// Child pid to parent pid, for every process on the system.
+#[cfg(target_os = "linux")]
+fn parent_map() -> HashMap<u32, u32> {
+    let entries = match std::fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return HashMap::new(),
+    };
+    entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| Some((pid, proc_stat(pid)?.1)))
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn parent_map() -> HashMap<u32, u32> {
+    let output = match std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=", "-o", "ppid="])
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return HashMap::new(),
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some((fields.next()?.parse().ok()?, fields.next()?.parse().ok()?))
+        })
+        .collect()
+}
+
+/// State and parent pid from `/proc/<pid>/stat`.
+#[cfg(target_os = "linux")]
+fn proc_stat(pid: u32) -> Option<(char, u32)> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name is in parentheses and may itself contain them
+    let mut fields = stat[stat.rfind(')')? + 1..].split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    let ppid = fields.next()?.parse().ok()?;
+    Some((state, ppid))
+}
+
+/// Whether a process still exists. Zombies waiting to be reaped don't count.
+pub fn is_alive(pid: u32) -> bool {
+    #[cfg(target_os = "linux")]
+    if let Some((state, _)) = proc_stat(pid) {
+        return state != 'Z';
+    }
+    let rc = unsafe { libc::kill(pid as i32, 0) };
+    rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Send `signal` to the process group led by `pid` and to each of `others`,
+/// which catches descendants that moved to a group of their own.
+pub fn signal(pid: u32, others: &[u32], signal: i32) {
+    unsafe {
+        libc::kill(-(pid as i32), signal);
+        libc::kill(pid as i32, signal);
+        for &other in others {
+            libc::kill(other as i32, signal);
+        }
+    }
+}
+
+/// Wait until none of `pids` is alive, or the deadline passes. `reap` is
+/// called on each check so the caller can collect its own child. Returns the
+/// processes still alive.
+pub fn wait_gone(pids: &[u32], deadline: Instant, mut reap: impl FnMut()) -> Vec<u32> {
+    loop {
+        reap();
+        let alive: Vec<u32> = pids.iter().copied().filter(|&p| is_alive(p)).collect();
+        if alive.is_empty() || Instant::now() >= deadline {
+            return alive;
+        }
+        thread::sleep(POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    #[test]
+    fn test_group_stop_reaches_grandchildren() {
+        // A shell with a grandchild, and one that escapes into its own session
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 60 & setsid sleep 60 & wait"])
+            .stdout(Stdio::null())
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut tree = descendants(pid);
+        while tree.len() < 2 && Instant::now() < deadline {
+            thread::sleep(POLL);
+            tree = descendants(pid);
+        }
+        assert_eq!(tree.len(), 2, "both sleeps are found");
+
+        signal(pid, &tree, libc::SIGKILL);
+        let mut all = tree.clone();
+        all.push(pid);
+        let survivors = wait_gone(&all, Instant::now() + Duration::from_secs(5), || {
+            let _ = child.try_wait();
+        });
+        assert!(survivors.is_empty(), "still alive: {:?}", survivors);
+    }
+}
@@ -1,28 +1,265 @@
 use anyhow::Result;
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::process::{Child, Command, Stdio};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(not(windows))]
+use crate::shell;
+use crate::health::{Health, Monitor};
+use crate::logs::{LogFile, LogSettings};
+use crate::ports;
+#[cfg(unix)]
+use crate::proc_tree;
+#[cfg(unix)]
+use crate::pty::Pty;
+use crate::store::{HealthCheck, RestartPolicy};
+use crate::supervisor::{ExitInfo, ProcessSnapshot, Request, RunRecord, SupervisorClient, REPLY_TIMEOUT};
+
+/// Captured output for one process. `dropped` counts lines trimmed from the
+/// front, so callers can follow the stream by absolute line number.
+#[derive(Default)]
+struct OutputBuffer {
+    lines: Vec<String>,
+    dropped: usize,
+    /// Ports the current run's output said it is serving on, in order
+    announced: Vec<u16>,
+}
+
+impl OutputBuffer {
+    fn push(&mut self, line: String) {
+        if let Some(port) = ports::announced_port(&line) {
+            if !self.announced.contains(&port) {
+                self.announced.push(port);
+            }
+        }
+        self.lines.push(line);
+        // Keep last 1000 lines
+        if self.lines.len() > 1000 {
+            self.lines.remove(0);
+            self.dropped += 1;
+        }
+    }
+
+    /// Lines after absolute line number `seen`, and the new total.
+    fn since(&self, seen: usize) -> (Vec<String>, usize) {
+        let skip = seen.saturating_sub(self.dropped).min(self.lines.len());
+        (self.lines[skip..].to_vec(), self.dropped + self.lines.len())
+    }
+}
+
+/// Everything needed to launch one managed process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchSpec {
+    pub cwd: PathBuf,
+    pub command: String,
+    #[serde(default)]
+    pub env: Vec<(String, String)>,
+    /// Exported to the process as `PORT`
+    pub port: Option<u16>,
+    /// Run through the user's login shell (`$SHELL -lc`) instead of parsing
+    /// the command ourselves. Windows always runs commands through `cmd.exe`.
+    #[serde(default)]
+    pub shell: bool,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+    /// Restarts allowed in a row before giving up; defaults to `DEFAULT_MAX_RESTARTS`
+    #[serde(default)]
+    pub max_restarts: Option<u32>,
+    /// Run under a pseudo-terminal instead of pipes, so the process behaves as
+    /// it does in a terminal. Stdout and stderr arrive as one stream.
+    #[serde(default)]
+    pub pty: bool,
+    /// Seconds between SIGTERM and SIGKILL when stopping; defaults to
+    /// `DEFAULT_STOP_TIMEOUT`
+    #[serde(default)]
+    pub stop_timeout: Option<u64>,
+    #[serde(default)]
+    pub health: Option<HealthCheck>,
+}
+
+impl LaunchSpec {
+    pub fn new(cwd: &Path, command: &str) -> Self {
+        Self {
+            cwd: cwd.to_path_buf(),
+            command: command.to_string(),
+            env: Vec::new(),
+            port: None,
+            shell: false,
+            restart: RestartPolicy::Never,
+            max_restarts: None,
+            pty: false,
+            stop_timeout: None,
+            health: None,
+        }
+    }
+}
+
+/// Grace period for a stopped process to exit before it is killed.
+pub const DEFAULT_STOP_TIMEOUT: u64 = 5;
+
+/// Restarts allowed in a row when a project doesn't set `max_restarts`.
+pub const DEFAULT_MAX_RESTARTS: u32 = 5;
+
+/// A process that stays up this long is considered healthy again, and its
+/// restart count starts over.
+const STABLE_AFTER: Duration = Duration::from_secs(30);
+
+/// Delay before the next restart: 1s, doubling with each restart, capped at 30s.
+fn restart_delay(restarts: u32) -> Duration {
+    Duration::from_secs((1u64 << restarts.min(5)).min(30))
+}
+
+/// Finished runs kept per process.
+const HISTORY_RUNS: usize = 10;
+
+/// Output lines kept with each finished run.
+const HISTORY_TAIL_LINES: usize = 100;
+
+/// How often to look for the ports running processes really bound.
+const PORT_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The signal that killed the process, if any.
+pub fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        status.signal()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
+/// How a process ended, e.g. "exit code 1" or "signal 9 (SIGKILL)".
+pub fn describe_exit(code: Option<i32>, signal: Option<i32>) -> String {
+    match (code, signal) {
+        (Some(code), _) => format!("exit code {}", code),
+        (None, Some(signal)) => {
+            let name = match signal {
+                1 => "SIGHUP",
+                2 => "SIGINT",
+                3 => "SIGQUIT",
+                6 => "SIGABRT",
+                9 => "SIGKILL",
+                11 => "SIGSEGV",
+                13 => "SIGPIPE",
+                15 => "SIGTERM",
+                _ => return format!("signal {}", signal),
+            };
+            format!("signal {} ({})", signal, name)
+        }
+        (None, None) => "unknown status".to_string(),
+    }
+}
+
+/// Short form of a duration in seconds, e.g. "45s", "3m 12s", "2h 5m".
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+    }
+}
+
+/// What `launch` was asked to run, kept so the process can be restarted.
+struct Launch {
+    spec: LaunchSpec,
+    started_at: Instant,
+    /// `started_at` as a Unix timestamp, for run history
+    started_unix: u64,
+    /// Output capture threads of the current run
+    readers: Vec<thread::JoinHandle<()>>,
+    /// Log file of the current run
+    log: Option<Arc<Mutex<LogFile>>>,
+    /// Terminal of the current run, for `pty` launches
+    #[cfg(unix)]
+    pty: Option<Pty>,
+    /// Stdin of the current run, while it is running
+    stdin: Option<Box<dyn Write + Send>>,
+    restarts: u32,
+    /// Set once the process has exited and is not running
+    exit: Option<ExitInfo>,
+    /// Health check of the current run
+    health: Option<Monitor>,
+    restart_at: Option<Instant>,
+}
 
 pub struct ProcessManager {
     processes: HashMap<String, Child>,
-    output_buffers: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    launches: HashMap<String, Launch>,
+    /// Finished runs per process, oldest first
+    history: HashMap<String, Vec<RunRecord>>,
+    output_buffers: Arc<Mutex<HashMap<String, OutputBuffer>>>,
+    /// Port each process serves on: the one it was given until it is seen
+    /// listening somewhere else
     ports: HashMap<String, u16>,
+    /// Processes seen listening on their port
+    confirmed_ports: HashSet<String>,
+    last_port_check: Instant,
     claude_pids: HashMap<String, u32>,
+    /// Where to write log files; None leaves output in memory only
+    logging: Option<LogSettings>,
+    /// Columns and rows of the pseudo-terminals of `pty` launches
+    pty_size: (u16, u16),
+    /// When attached, dev servers live in the supervisor daemon and every
+    /// call below is forwarded to it. Claude terminals always stay local.
+    supervisor: Option<SupervisorClient>,
 }
 
 impl ProcessManager {
     pub fn new() -> Self {
         Self {
             processes: HashMap::new(),
+            launches: HashMap::new(),
+            history: HashMap::new(),
             output_buffers: Arc::new(Mutex::new(HashMap::new())),
             ports: HashMap::new(),
+            confirmed_ports: HashSet::new(),
+            last_port_check: Instant::now(),
             claude_pids: HashMap::new(),
+            logging: None,
+            // Until a client reports the size of its logs pane
+            pty_size: (80, 24),
+            supervisor: None,
+        }
+    }
+
+    /// Attach to a running supervisor daemon if there is one, otherwise manage
+    /// processes in-process like `new()`.
+    pub fn attach() -> Self {
+        Self {
+            supervisor: SupervisorClient::connect(),
+            ..Self::new()
         }
     }
 
+    /// Also write output to log files under `~/.claude-manager/logs`. When
+    /// attached, the supervisor writes them instead.
+    pub fn set_logging(&mut self, settings: &LogSettings) {
+        self.logging = settings.enabled.then(|| settings.clone());
+    }
+
+    /// True when dev servers are owned by the supervisor and outlive this process.
+    pub fn is_attached(&self) -> bool {
+        self.supervisor.is_some()
+    }
+
     #[allow(dead_code)]
     pub fn start(&mut self, project_name: &str, cwd: &Path, command: &str) -> Result<()> {
         self.start_with_port(project_name, cwd, command, None)
@@ -35,9 +272,62 @@ impl ProcessManager {
         command: &str,
         port: Option<u16>,
     ) -> Result<()> {
-        // Parse command into program and args
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        if parts.is_empty() {
+        let spec = LaunchSpec {
+            port,
+            ..LaunchSpec::new(cwd, command)
+        };
+        self.launch(project_name, &spec)
+    }
+
+    /// Start a process under `key` (a project name, or `project/service`).
+    pub fn launch(&mut self, key: &str, spec: &LaunchSpec) -> Result<()> {
+        if let Some(ref mut supervisor) = self.supervisor {
+            supervisor.send(
+                &Request::Start {
+                    name: key.to_string(),
+                    spec: spec.clone(),
+                },
+                REPLY_TIMEOUT,
+            )?;
+            return supervisor.refresh();
+        }
+
+        {
+            let mut buffers = self.output_buffers.lock().unwrap();
+            buffers.insert(key.to_string(), OutputBuffer::default());
+        }
+        self.launches.insert(
+            key.to_string(),
+            Launch {
+                spec: spec.clone(),
+                started_at: Instant::now(),
+                started_unix: unix_now(),
+                readers: Vec::new(),
+                log: None,
+                #[cfg(unix)]
+                pty: None,
+                stdin: None,
+                restarts: 0,
+                exit: None,
+                health: None,
+                restart_at: None,
+            },
+        );
+        let result = self.spawn(key, spec);
+        if result.is_err() {
+            self.launches.remove(key);
+        }
+        result
+    }
+
+    /// Spawn the process and capture its output, appending to any output kept
+    /// from an earlier run under the same key.
+    fn spawn(&mut self, key: &str, spec: &LaunchSpec) -> Result<()> {
+        let command = spec.command.as_str();
+        let cwd = spec.cwd.as_path();
+        let port = spec.port;
+
+        if command.trim().is_empty() {
             anyhow::bail!("Empty command");
         }
 
@@ -45,7 +335,7 @@ impl ProcessManager {
 
         // On Windows, commands like npm/pnpm/yarn/bun are .cmd files, not .exe.
         // We must run them through cmd.exe to resolve them properly.
-        // Also use CREATE_NO_WINDOW and null stdin to avoid interfering with the TUI console.
+        // Also use CREATE_NO_WINDOW and piped stdin to avoid interfering with the TUI console.
         #[cfg(windows)]
         {
             use std::os::windows::process::CommandExt;
@@ -54,156 +344,384 @@ impl ProcessManager {
             cmd = Command::new("cmd.exe");
             cmd.args(["/c", command])
                 .current_dir(cwd)
-                .stdin(Stdio::null())
+                .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
                 .creation_flags(CREATE_NO_WINDOW);
         }
 
+        #[cfg(not(windows))]
+        let invocation = if spec.shell {
+            shell::login_shell(command)
+        } else {
+            shell::parse(command)?
+        };
+
         #[cfg(not(windows))]
         {
-            let program = parts[0];
-            let args = &parts[1..];
-            cmd = Command::new(program);
-            cmd.args(args)
+            cmd = Command::new(&invocation.program);
+            cmd.args(&invocation.args)
                 .current_dir(cwd)
+                .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped());
         }
 
+        cmd.envs(spec.env.iter().map(|(k, v)| (k, v)));
+
         // Set PORT env var if provided (for Node.js projects)
         if let Some(p) = port {
             cmd.env("PORT", p.to_string());
         }
 
+        // Assignments prefixing the command (`FOO=1 npm run dev`) win, as they
+        // would in a shell
+        #[cfg(not(windows))]
+        cmd.envs(invocation.env);
+
+        // Its own process group, so stopping it reaches everything it started.
+        // A pseudo-terminal gives it a session of its own instead.
+        #[cfg(unix)]
+        let pty = match spec.pty {
+            true => Some(Pty::attach(&mut cmd, self.pty_size)?),
+            false => {
+                use std::os::unix::process::CommandExt;
+                cmd.process_group(0);
+                None
+            }
+        };
+
         let mut child = cmd.spawn()?;
+        // Close our copies of the terminal, so its output ends with the process
+        drop(cmd);
 
         // Setup output capture
-        let stdout = child.stdout.take();
+        let stdout: Option<Box<dyn Read + Send>> = child.stdout.take().map(|s| Box::new(s) as _);
         let stderr = child.stderr.take();
+        let stdin: Option<Box<dyn Write + Send>> = child.stdin.take().map(|s| Box::new(s) as _);
+        #[cfg(unix)]
+        let (stdout, stdin) = match pty {
+            Some(ref pty) => (Some(Box::new(pty.handle()?) as _), Some(Box::new(pty.handle()?) as _)),
+            None => (stdout, stdin),
+        };
 
-        let key = project_name.to_string();
+        let key = key.to_string();
 
-        {
+        // Output of this run starts after whatever earlier runs left
+        let seen = {
             let mut buffers = self.output_buffers.lock().unwrap();
-            buffers.insert(key.clone(), Vec::new());
-        }
+            let buf = buffers.entry(key.clone()).or_default();
+            buf.announced.clear();
+            buf.dropped + buf.lines.len()
+        };
+
+        // Each run gets its own log file; failing to create one isn't fatal
+        let log = self
+            .logging
+            .as_ref()
+            .and_then(|settings| LogFile::create(&key, settings).ok())
+            .map(|log| Arc::new(Mutex::new(log)));
 
         // Spawn threads to capture output
+        let mut readers = Vec::new();
         if let Some(stdout) = stdout {
-            let buffers = Arc::clone(&self.output_buffers);
-            let key = key.clone();
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines().map_while(Result::ok) {
-                    if let Ok(mut buffers) = buffers.lock() {
-                        if let Some(buf) = buffers.get_mut(&key) {
-                            buf.push(line);
-                            // Keep last 1000 lines
-                            if buf.len() > 1000 {
-                                buf.remove(0);
-                            }
-                        }
-                    }
-                }
-            });
+            readers.push(self.capture(&key, stdout, "", log.clone()));
         }
-
         if let Some(stderr) = stderr {
-            let buffers = Arc::clone(&self.output_buffers);
-            let key = key.clone();
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines().map_while(Result::ok) {
-                    if let Ok(mut buffers) = buffers.lock() {
-                        if let Some(buf) = buffers.get_mut(&key) {
-                            buf.push(format!("[stderr] {}", line));
-                            if buf.len() > 1000 {
-                                buf.remove(0);
-                            }
-                        }
-                    }
-                }
-            });
+            readers.push(self.capture(&key, stderr, "[stderr] ", log.clone()));
         }
 
+        if let Some(launch) = self.launches.get_mut(&key) {
+            launch.started_at = Instant::now();
+            launch.started_unix = unix_now();
+            launch.readers = readers;
+            launch.log = log;
+            launch.stdin = stdin;
+            launch.health = spec.health.as_ref().map(|check| Monitor::new(check, seen));
+            #[cfg(unix)]
+            {
+                launch.pty = pty;
+            }
+        }
         self.processes.insert(key.clone(), child);
+        self.confirmed_ports.remove(&key);
         if let Some(p) = port {
             self.ports.insert(key, p);
         }
         Ok(())
     }
 
+    /// Read lines from one output stream into the process's buffer and log
+    /// file, each marked with `prefix`.
+    fn capture(
+        &self,
+        key: &str,
+        stream: impl Read + Send + 'static,
+        prefix: &'static str,
+        log: Option<Arc<Mutex<LogFile>>>,
+    ) -> thread::JoinHandle<()> {
+        let buffers = Arc::clone(&self.output_buffers);
+        let key = key.to_string();
+        thread::spawn(move || {
+            let reader = BufReader::new(stream);
+            for line in reader.lines().map_while(Result::ok) {
+                let line = format!("{}{}", prefix, line);
+                if let Some(ref log) = log {
+                    if let Ok(mut log) = log.lock() {
+                        log.write_line(&line);
+                    }
+                }
+                if let Ok(mut buffers) = buffers.lock() {
+                    if let Some(buf) = buffers.get_mut(&key) {
+                        buf.push(line);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Write to a running process's stdin, such as a key a dev server reacts
+    /// to. In a pseudo-terminal, a newline is sent as Enter would be (`\r`).
+    pub fn send_input(&mut self, project_name: &str, data: &str) -> Result<()> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor.send(
+                &Request::Input {
+                    name: project_name.to_string(),
+                    data: data.to_string(),
+                },
+                REPLY_TIMEOUT,
+            );
+        }
+
+        let launch = self.launches.get_mut(project_name);
+        let (stdin, data) = match launch {
+            #[cfg(unix)]
+            Some(Launch { stdin: Some(stdin), pty: Some(_), .. }) => (stdin, data.replace('\n', "\r")),
+            Some(Launch { stdin: Some(stdin), .. }) => (stdin, data.to_string()),
+            _ => anyhow::bail!("{} is not running", project_name),
+        };
+        stdin.write_all(data.as_bytes())?;
+        stdin.flush()?;
+        Ok(())
+    }
+
+    /// Set the terminal size of `pty` launches, e.g. to the size of the pane
+    /// their output is shown in. Processes get `SIGWINCH` and redraw.
+    pub fn resize_ptys(&mut self, cols: u16, rows: u16) {
+        if self.pty_size == (cols, rows) {
+            return;
+        }
+        self.pty_size = (cols, rows);
+
+        if let Some(ref supervisor) = self.supervisor {
+            let _ = supervisor.send(&Request::Resize { cols, rows }, REPLY_TIMEOUT);
+            return;
+        }
+        #[cfg(unix)]
+        for pty in self.launches.values().filter_map(|l| l.pty.as_ref()) {
+            let _ = pty.resize(self.pty_size);
+        }
+    }
+
     pub fn set_claude_pid(&mut self, project_name: &str, pid: u32) {
         self.claude_pids.insert(project_name.to_string(), pid);
     }
 
-    fn kill_claude_terminal(&mut self, project_name: &str) {
-        if let Some(pid) = self.claude_pids.remove(project_name) {
-            #[cfg(windows)]
-            {
-                use std::process::Command as StdCommand;
-                let _ = StdCommand::new("taskkill")
-                    .args(["/PID", &pid.to_string(), "/T", "/F"])
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .status();
-            }
+    /// Ask a Claude terminal's process group to exit. `kill_claude_terminals`
+    /// finishes the job.
+    fn signal_claude_terminal(&mut self, project_name: &str) -> Option<u32> {
+        let pid = self.claude_pids.remove(project_name)?;
 
-            #[cfg(unix)]
-            {
-                unsafe {
-                    libc::kill(-(pid as i32), libc::SIGTERM);
-                }
-                std::thread::sleep(std::time::Duration::from_millis(200));
+        #[cfg(windows)]
+        {
+            use std::process::Command as StdCommand;
+            let _ = StdCommand::new("taskkill")
+                .args(["/PID", &pid.to_string(), "/T", "/F"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        }
+
+        #[cfg(unix)]
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGTERM);
+        }
+        Some(pid)
+    }
+
+    /// SIGKILL whatever is left of Claude terminals signalled a moment ago.
+    fn kill_claude_terminals(pids: &[u32]) {
+        #[cfg(unix)]
+        if !pids.is_empty() {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            for &pid in pids {
                 unsafe {
                     libc::kill(-(pid as i32), libc::SIGKILL);
                 }
             }
         }
+
+        #[cfg(windows)]
+        let _ = pids;
     }
 
     pub fn stop(&mut self, project_name: &str) -> Result<()> {
-        // Kill dev server
-        if let Some(mut child) = self.processes.remove(project_name) {
-            #[cfg(unix)]
-            {
-                unsafe {
-                    libc::kill(child.id() as i32, libc::SIGTERM);
-                }
+        self.stop_many(&[project_name.to_string()])
+    }
+
+    /// Stop several processes at once: every one is signalled before waiting
+    /// on any, so they shut down side by side instead of one grace period
+    /// after another.
+    pub fn stop_many(&mut self, names: &[String]) -> Result<()> {
+        let pending = self.stop_in_background(names);
+        self.wait_stop(pending)
+    }
+
+    /// Like `stop_many`, but the waiting happens on a worker thread, so the
+    /// caller stays responsive. Hand the result to `poll_stop` until it is
+    /// done.
+    pub fn stop_in_background(&mut self, names: &[String]) -> PendingStop {
+        let (tx, rx) = mpsc::channel();
+        let supervised = self
+            .supervisor
+            .as_ref()
+            .map(|supervisor| (supervisor.clone(), supervisor.stop_timeout(names)));
+
+        match supervised {
+            Some((supervisor, timeout)) => {
+                let claude: Vec<u32> = names
+                    .iter()
+                    .filter_map(|name| self.signal_claude_terminal(name))
+                    .collect();
+                let request = Request::StopMany { names: names.to_vec() };
+                thread::spawn(move || {
+                    let result = supervisor.send(&request, timeout);
+                    Self::kill_claude_terminals(&claude);
+                    let _ = tx.send(Stopped::Elsewhere(result));
+                });
             }
-            #[cfg(windows)]
-            {
-                // taskkill /T kills the entire process tree (cmd.exe + node, etc.)
-                let _ = Command::new("taskkill")
-                    .args(["/PID", &child.id().to_string(), "/T", "/F"])
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .status();
+            None => {
+                let mut stopping = self.begin_stop(names);
+                thread::spawn(move || {
+                    stopping.wait();
+                    let _ = tx.send(Stopped::Local(stopping));
+                });
             }
-            std::thread::sleep(std::time::Duration::from_millis(500));
-            let _ = child.kill();
-            let _ = child.wait();
         }
 
-        // Kill Claude terminal
-        self.kill_claude_terminal(project_name);
+        PendingStop {
+            names: names.to_vec(),
+            rx,
+        }
+    }
+
+    /// How a background stop went, once it is done; None while it is still
+    /// waiting for the processes to exit.
+    pub fn poll_stop(&mut self, pending: &PendingStop) -> Option<Result<()>> {
+        match pending.rx.try_recv() {
+            Ok(stopped) => Some(self.record_stop(stopped)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(anyhow::anyhow!(
+                "Stopping {} was interrupted",
+                pending.names.join(", ")
+            ))),
+        }
+    }
+
+    /// Block until a background stop is done.
+    pub fn wait_stop(&mut self, pending: PendingStop) -> Result<()> {
+        match pending.rx.recv() {
+            Ok(stopped) => self.record_stop(stopped),
+            Err(_) => anyhow::bail!("Stopping {} was interrupted", pending.names.join(", ")),
+        }
+    }
 
-        // Verify port is freed
-        if let Some(port) = self.ports.remove(project_name) {
-            for _ in 0..10 {
-                if !Self::is_port_open(port) {
-                    break;
+    fn record_stop(&mut self, stopped: Stopped) -> Result<()> {
+        match stopped {
+            Stopped::Local(stopping) => self.finish_stop(stopping),
+            Stopped::Elsewhere(result) => {
+                if let Some(ref mut supervisor) = self.supervisor {
+                    let _ = supervisor.refresh();
                 }
-                std::thread::sleep(std::time::Duration::from_millis(200));
+                result
+            }
+        }
+    }
+
+    /// Stop every running process and Claude terminal. When attached, dev
+    /// servers belong to the supervisor and keep running; only the Claude
+    /// terminals, which are always local, are closed.
+    pub fn stop_all(&mut self) -> Result<()> {
+        if self.supervisor.is_some() {
+            let names: Vec<String> = self.claude_pids.keys().cloned().collect();
+            let claude: Vec<u32> = names
+                .iter()
+                .filter_map(|name| self.signal_claude_terminal(name))
+                .collect();
+            Self::kill_claude_terminals(&claude);
+            return Ok(());
+        }
+        let mut names = self.running_projects();
+        names.extend(self.claude_pids.keys().filter(|k| !names.contains(k)).cloned().collect::<Vec<_>>());
+        self.stop_many(&names)
+    }
+
+    /// First half of a stop: forget the processes, so they aren't restarted,
+    /// and send them SIGTERM. Waiting happens in `Stopping::wait`, which
+    /// doesn't need the manager.
+    pub fn begin_stop(&mut self, names: &[String]) -> Stopping {
+        let mut stopping = Stopping {
+            names: names.to_vec(),
+            processes: Vec::new(),
+            claude: Vec::new(),
+            ports: Vec::new(),
+        };
+        for name in names {
+            let launch = self.launches.remove(name);
+            if let Some(child) = self.processes.remove(name) {
+                let grace = launch
+                    .as_ref()
+                    .and_then(|l| l.spec.stop_timeout)
+                    .unwrap_or(DEFAULT_STOP_TIMEOUT);
+                stopping.processes.push(StoppingProcess::signal(name, child, launch, Duration::from_secs(grace)));
             }
+            stopping.claude.extend(self.signal_claude_terminal(name));
+            stopping.ports.extend(self.ports.remove(name));
+            self.confirmed_ports.remove(name);
         }
+        stopping
+    }
 
-        // Clean up buffer
+    /// Second half of a stop: record the finished runs and drop their output.
+    pub fn finish_stop(&mut self, stopping: Stopping) -> Result<()> {
+        let mut errors = Vec::new();
+        for process in &stopping.processes {
+            if let (Some(status), Some(launch)) = (&process.status, &process.launch) {
+                let record = self.run_record(&process.name, launch, status, true);
+                self.push_history(&process.name, record);
+            }
+            if !process.survivors.is_empty() {
+                let pids: Vec<String> = process.survivors.iter().map(|p| p.to_string()).collect();
+                errors.push(format!(
+                    "Stopped {}, but some of its processes survived SIGKILL (pid {})",
+                    process.name,
+                    pids.join(", ")
+                ));
+            }
+        }
+
+        // Clean up buffers, unless the process was started again meanwhile
         if let Ok(mut buffers) = self.output_buffers.lock() {
-            buffers.remove(project_name);
+            for name in &stopping.names {
+                if !self.processes.contains_key(name) {
+                    buffers.remove(name);
+                }
+            }
         }
 
+        if !errors.is_empty() {
+            anyhow::bail!(errors.join("; "));
+        }
         Ok(())
     }
 
@@ -217,39 +735,394 @@ impl ProcessManager {
     }
 
     pub fn is_running(&self, project_name: &str) -> bool {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor.processes().iter().any(|p| p.name == project_name);
+        }
         self.processes.contains_key(project_name)
     }
 
     pub fn reap_dead(&mut self) {
-        let dead: Vec<String> = self
+        if let Some(ref mut supervisor) = self.supervisor {
+            // The supervisor reaps its own children; just pick up the result.
+            // If it has gone away, its processes went with it.
+            if supervisor.refresh().is_err() {
+                self.supervisor = None;
+            }
+            return;
+        }
+
+        let dead: Vec<(String, ExitStatus)> = self
             .processes
             .iter_mut()
             .filter_map(|(name, child)| match child.try_wait() {
-                Ok(Some(_)) => Some(name.clone()),
+                Ok(Some(status)) => Some((name.clone(), status)),
                 _ => None,
             })
             .collect();
-        for name in dead {
+        for (name, status) in dead {
             self.processes.remove(&name);
             self.ports.remove(&name);
+            self.confirmed_ports.remove(&name);
+            if let Some(launch) = self.launches.get_mut(&name) {
+                launch.stdin = None;
+            }
+            let record = self
+                .launches
+                .get(&name)
+                .map(|l| self.run_record(&name, l, &status, false));
+            if let Some(record) = record {
+                self.push_history(&name, record);
+            }
+            self.handle_exit(&name, status.code(), exit_signal(&status), !status.success());
+        }
+
+        // Restart whatever is due
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .launches
+            .iter()
+            .filter(|(_, l)| l.restart_at.is_some_and(|at| at <= now))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in due {
+            let launch = self.launches.get_mut(&name).unwrap();
+            launch.restart_at = None;
+            launch.restarts += 1;
+            let spec = launch.spec.clone();
+
+            match self.spawn(&name, &spec) {
+                Ok(()) => {
+                    if let Some(launch) = self.launches.get_mut(&name) {
+                        launch.exit = None;
+                    }
+                }
+                Err(e) => {
+                    self.log_event(&name, &format!("restart failed: {}", e));
+                    self.handle_exit(&name, None, None, true);
+                }
+            }
+        }
+
+        if self.last_port_check.elapsed() >= PORT_CHECK_INTERVAL {
+            self.last_port_check = Instant::now();
+            self.discover_ports();
+        }
+        self.check_health();
+    }
+
+    /// Advance the health check of every running process.
+    fn check_health(&mut self) {
+        for (name, launch) in self.launches.iter_mut() {
+            let monitor = match launch.health {
+                Some(ref mut monitor) if self.processes.contains_key(name) => monitor,
+                _ => continue,
+            };
+            let port = self.ports.get(name).copied();
+            let buffers = &self.output_buffers;
+            monitor.poll(port, |seen| match buffers.lock().unwrap().get(name) {
+                Some(buf) => buf.since(seen),
+                None => (Vec::new(), seen),
+            });
+        }
+    }
+
+    /// Record the port each process really serves on, which may not be the
+    /// one it was given: what its output announced, checked against the
+    /// ports its process tree is listening on.
+    fn discover_ports(&mut self) {
+        let announced: HashMap<String, Vec<u16>> = {
+            let buffers = self.output_buffers.lock().unwrap();
+            buffers
+                .iter()
+                .map(|(name, buf)| (name.clone(), buf.announced.clone()))
+                .collect()
+        };
+        for (name, child) in &self.processes {
+            #[allow(unused_mut)]
+            let mut tree = vec![child.id()];
+            #[cfg(unix)]
+            tree.extend(proc_tree::descendants(child.id()));
+
+            let assigned = self.launches.get(name).and_then(|l| l.spec.port);
+            let announced = announced.get(name).map(Vec::as_slice).unwrap_or_default();
+            if let Some((port, confirmed)) = ports::actual_port(announced, assigned, &ports::listening_ports(&tree)) {
+                self.ports.insert(name.clone(), port);
+                if confirmed {
+                    self.confirmed_ports.insert(name.clone());
+                } else {
+                    self.confirmed_ports.remove(name);
+                }
+            }
+        }
+    }
+
+    /// Apply the restart policy to a process that just exited.
+    fn handle_exit(&mut self, name: &str, code: Option<i32>, signal: Option<i32>, failed: bool) {
+        let launch = match self.launches.get_mut(name) {
+            Some(l) => l,
+            None => return,
+        };
+        if launch.started_at.elapsed() >= STABLE_AFTER {
+            launch.restarts = 0;
+        }
+
+        let wanted = match launch.spec.restart {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => failed,
+            RestartPolicy::Always => true,
+        };
+        let max = launch.spec.max_restarts.unwrap_or(DEFAULT_MAX_RESTARTS);
+        let restarting = wanted && launch.restarts < max;
+
+        let how = format!("exited ({})", describe_exit(code, signal));
+        let event = if restarting {
+            let delay = restart_delay(launch.restarts);
+            launch.restart_at = Some(Instant::now() + delay);
+            format!(
+                "{}; restarting in {}s ({}/{})",
+                how,
+                delay.as_secs(),
+                launch.restarts + 1,
+                max
+            )
+        } else if wanted {
+            format!("{}; giving up after {} restarts", how, launch.restarts)
+        } else {
+            how
+        };
+
+        if failed || restarting {
+            launch.exit = Some(ExitInfo {
+                name: name.to_string(),
+                code,
+                signal,
+                restarts: launch.restarts,
+                restarting,
+            });
+        } else {
+            self.launches.remove(name);
+        }
+
+        if failed || wanted {
+            self.log_event(name, &event);
+        }
+    }
+
+    /// Add a line from the manager itself to a process's output and log file.
+    fn log_event(&self, name: &str, event: &str) {
+        let line = format!("[claude-manager] {}", event);
+        let log = self.launches.get(name).and_then(|l| l.log.as_ref());
+        if let Some(Ok(mut log)) = log.map(|l| l.lock()) {
+            log.write_line(&line);
+        }
+        if let Ok(mut buffers) = self.output_buffers.lock() {
+            buffers.entry(name.to_string()).or_default().push(line);
+        }
+    }
+
+    /// Record of a run that just ended. Waits briefly for the capture threads,
+    /// so the tail includes the last lines the process wrote.
+    fn run_record(&self, name: &str, launch: &Launch, status: &ExitStatus, stopped: bool) -> RunRecord {
+        let deadline = Instant::now() + Duration::from_millis(200);
+        while launch.readers.iter().any(|r| !r.is_finished()) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let tail = match self.output_buffers.lock() {
+            Ok(buffers) => buffers
+                .get(name)
+                .map(|buf| buf.lines[buf.lines.len().saturating_sub(HISTORY_TAIL_LINES)..].to_vec())
+                .unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+
+        RunRecord {
+            started_at: launch.started_unix,
+            ended_at: unix_now(),
+            code: status.code(),
+            signal: exit_signal(status),
+            stopped,
+            tail,
+            log_file: launch
+                .log
+                .as_ref()
+                .and_then(|l| l.lock().ok().map(|l| l.path().to_path_buf())),
+        }
+    }
+
+    fn push_history(&mut self, name: &str, record: RunRecord) {
+        let runs = self.history.entry(name.to_string()).or_default();
+        runs.push(record);
+        if runs.len() > HISTORY_RUNS {
+            runs.remove(0);
+        }
+    }
+
+    /// Finished runs of a process, oldest first.
+    pub fn history(&self, project_name: &str) -> Vec<RunRecord> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor.history(project_name);
+        }
+        self.history.get(project_name).cloned().unwrap_or_default()
+    }
+
+    /// The most recent finished run of a process.
+    pub fn last_run(&self, project_name: &str) -> Option<RunRecord> {
+        self.history(project_name).pop()
+    }
+
+    /// How a process that isn't running last exited, if it crashed or is
+    /// waiting to be restarted.
+    pub fn exit_info(&self, project_name: &str) -> Option<ExitInfo> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor
+                .exited()
+                .iter()
+                .find(|e| e.name == project_name)
+                .cloned();
+        }
+        self.launches
+            .get(project_name)
+            .and_then(|l| l.exit.clone())
+    }
+
+    /// True while a crashed process is waiting for its next restart.
+    pub fn is_restarting(&self, project_name: &str) -> bool {
+        self.exit_info(project_name).is_some_and(|e| e.restarting)
+    }
+
+    /// Times a running process has been restarted since it last ran stably.
+    pub fn restarts(&self, project_name: &str) -> u32 {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor
+                .processes()
+                .iter()
+                .find(|p| p.name == project_name)
+                .map(|p| p.restarts)
+                .unwrap_or(0);
+        }
+        match self.launches.get(project_name) {
+            Some(l) if self.processes.contains_key(project_name) => l.restarts,
+            _ => 0,
         }
     }
 
     pub fn get_output(&self, project_name: &str) -> Vec<String> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor.output_since(project_name, 0).0;
+        }
         if let Ok(buffers) = self.output_buffers.lock() {
-            buffers.get(project_name).cloned().unwrap_or_default()
+            buffers
+                .get(project_name)
+                .map(|b| b.lines.clone())
+                .unwrap_or_default()
         } else {
             Vec::new()
         }
     }
 
+    /// Lines captured after the first `seen` lines, plus the new total line count.
+    /// Lets a caller stream output incrementally even after old lines are trimmed.
+    pub fn get_output_since(&self, project_name: &str, seen: usize) -> (Vec<String>, usize) {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor.output_since(project_name, seen);
+        }
+        let buffers = match self.output_buffers.lock() {
+            Ok(b) => b,
+            Err(_) => return (Vec::new(), seen),
+        };
+        match buffers.get(project_name) {
+            Some(buf) => buf.since(seen),
+            None => (Vec::new(), seen),
+        }
+    }
+
     pub fn running_projects(&self) -> Vec<String> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor.processes().iter().map(|p| p.name.clone()).collect();
+        }
         self.processes.keys().cloned().collect()
     }
 
     pub fn get_port(&self, project_name: &str) -> Option<u16> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor
+                .processes()
+                .iter()
+                .find(|p| p.name == project_name)
+                .and_then(|p| p.port);
+        }
         self.ports.get(project_name).copied()
     }
+
+    /// Whether a process has been seen listening on the port `get_port`
+    /// reports, rather than the port being the one it was given or announced.
+    pub fn port_confirmed(&self, project_name: &str) -> bool {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor
+                .processes()
+                .iter()
+                .any(|p| p.name == project_name && p.port_confirmed);
+        }
+        self.confirmed_ports.contains(project_name)
+    }
+
+    /// Health of a running process with a health check.
+    pub fn health(&self, project_name: &str) -> Option<Health> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor
+                .processes()
+                .iter()
+                .find(|p| p.name == project_name)
+                .and_then(|p| p.health.clone());
+        }
+        if !self.processes.contains_key(project_name) {
+            return None;
+        }
+        let monitor = self.launches.get(project_name)?.health.as_ref()?;
+        Some(monitor.state().clone())
+    }
+
+    pub fn get_pid(&self, project_name: &str) -> Option<u32> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor
+                .processes()
+                .iter()
+                .find(|p| p.name == project_name)
+                .map(|p| p.pid);
+        }
+        self.processes.get(project_name).map(|c| c.id())
+    }
+
+    /// Runtime state of every running process, for reporting to supervisor clients.
+    pub fn snapshot(&self) -> Vec<ProcessSnapshot> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor.processes().to_vec();
+        }
+        self.processes
+            .iter()
+            .map(|(name, child)| ProcessSnapshot {
+                name: name.clone(),
+                pid: child.id(),
+                port: self.ports.get(name).copied(),
+                port_confirmed: self.confirmed_ports.contains(name),
+                restarts: self.launches.get(name).map(|l| l.restarts).unwrap_or(0),
+                health: self.health(name),
+                stop_timeout: self.launches.get(name).and_then(|l| l.spec.stop_timeout),
+            })
+            .collect()
+    }
+
+    /// Processes that crashed or are waiting to be restarted.
+    pub fn exited(&self) -> Vec<ExitInfo> {
+        if let Some(ref supervisor) = self.supervisor {
+            return supervisor.exited().to_vec();
+        }
+        self.launches
+            .values()
+            .filter_map(|l| l.exit.clone())
+            .collect()
+    }
 }
 
 impl Default for ProcessManager {
@@ -258,6 +1131,160 @@ impl Default for ProcessManager {
     }
 }
 
+/// A stop waiting on a worker thread; see `ProcessManager::stop_in_background`.
+pub struct PendingStop {
+    names: Vec<String>,
+    rx: Receiver<Stopped>,
+}
+
+enum Stopped {
+    /// Our own processes, still to be recorded by `finish_stop`
+    Local(Stopping),
+    /// Stopped by the supervisor, or not a process we manage
+    Elsewhere(Result<()>),
+}
+
+impl PendingStop {
+    /// Kill a process we don't manage, such as whatever holds a port.
+    pub fn kill(pid: u32) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(Stopped::Elsewhere(ports::kill(pid)));
+        });
+        Self { names: Vec::new(), rx }
+    }
+
+    /// Process keys being stopped.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+/// Processes sent SIGTERM by `ProcessManager::begin_stop`.
+pub struct Stopping {
+    names: Vec<String>,
+    processes: Vec<StoppingProcess>,
+    /// Claude terminals
+    claude: Vec<u32>,
+    ports: Vec<u16>,
+}
+
+struct StoppingProcess {
+    name: String,
+    child: Child,
+    launch: Option<Launch>,
+    /// The process and its descendants
+    tree: Vec<u32>,
+    /// When to give up on SIGTERM
+    kill_at: Instant,
+    /// Set once the process has exited
+    status: Option<ExitStatus>,
+    /// Processes still alive after SIGKILL
+    survivors: Vec<u32>,
+}
+
+impl StoppingProcess {
+    /// SIGTERM to the process group and every descendant.
+    #[cfg(unix)]
+    fn signal(name: &str, child: Child, launch: Option<Launch>, grace: Duration) -> Self {
+        let pid = child.id();
+        let mut tree = proc_tree::descendants(pid);
+        tree.push(pid);
+        proc_tree::signal(pid, &tree, libc::SIGTERM);
+        Self {
+            name: name.to_string(),
+            child,
+            launch,
+            tree,
+            kill_at: Instant::now() + grace,
+            status: None,
+            survivors: Vec::new(),
+        }
+    }
+
+    /// taskkill /T kills the entire process tree (cmd.exe + node, etc.)
+    #[cfg(windows)]
+    fn signal(name: &str, child: Child, launch: Option<Launch>, _grace: Duration) -> Self {
+        let _ = Command::new("taskkill")
+            .args(["/PID", &child.id().to_string(), "/T", "/F"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        Self {
+            name: name.to_string(),
+            child,
+            launch,
+            tree: Vec::new(),
+            kill_at: Instant::now(),
+            status: None,
+            survivors: Vec::new(),
+        }
+    }
+}
+
+impl Stopping {
+    /// Wait for the processes to exit, SIGKILLing each one still around after
+    /// its grace period, then for their ports to be released.
+    pub fn wait(&mut self) {
+        self.terminate();
+
+        for process in &mut self.processes {
+            if !process.survivors.contains(&process.child.id()) {
+                process.status = process.child.wait().ok();
+            }
+        }
+        ProcessManager::kill_claude_terminals(&self.claude);
+
+        // Verify ports are freed
+        for _ in 0..10 {
+            if !self.ports.iter().any(|&port| ProcessManager::is_port_open(port)) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        }
+    }
+
+    #[cfg(unix)]
+    fn terminate(&mut self) {
+        let mut order: Vec<(Instant, u32, Vec<u32>)> = self
+            .processes
+            .iter()
+            .map(|p| (p.kill_at, p.child.id(), p.tree.clone()))
+            .collect();
+        order.sort_by_key(|(kill_at, _, _)| *kill_at);
+
+        let processes = &mut self.processes;
+        let mut reap = || {
+            for process in processes.iter_mut() {
+                let _ = process.child.try_wait();
+            }
+        };
+        // Processes keep exiting while the earliest deadline is waited on, so
+        // this takes as long as the longest grace period, not their sum
+        let mut killed = Vec::new();
+        for (kill_at, pid, tree) in order {
+            let alive = proc_tree::wait_gone(&tree, kill_at, &mut reap);
+            if !alive.is_empty() {
+                proc_tree::signal(pid, &alive, libc::SIGKILL);
+                killed.extend(alive);
+            }
+        }
+        let survivors = proc_tree::wait_gone(&killed, Instant::now() + Duration::from_secs(1), reap);
+
+        for process in &mut self.processes {
+            process.survivors = process.tree.iter().copied().filter(|p| survivors.contains(p)).collect();
+        }
+    }
+
+    #[cfg(windows)]
+    fn terminate(&mut self) {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        for process in &mut self.processes {
+            let _ = process.child.kill();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,6 +1344,21 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_port_announced_in_output() {
+        let mut pm = ProcessManager::new();
+        let command = "sh -c 'echo \"Local: http://localhost:4999/\"; sleep 5'";
+        pm.start_with_port("announced", &env::temp_dir(), command, Some(3000)).unwrap();
+        assert_eq!(pm.get_port("announced"), Some(3000));
+
+        // Nothing is listening, so the announcement is taken at its word
+        std::thread::sleep(PORT_CHECK_INTERVAL);
+        pm.reap_dead();
+        assert_eq!(pm.get_port("announced"), Some(4999));
+        pm.stop("announced").unwrap();
+    }
+
     #[test]
     fn test_start_long_running_and_stop() {
         let mut pm = ProcessManager::new();
@@ -338,4 +1380,210 @@ mod tests {
 
         assert!(!pm.is_running("long-running"), "Process should be stopped");
     }
+
+    #[test]
+    fn test_on_failure_restarts_with_cap() {
+        let mut pm = ProcessManager::new();
+
+        #[cfg(windows)]
+        let command = "echo tick & exit 3";
+        #[cfg(not(windows))]
+        let command = "sh -c 'echo tick; exit 3'";
+
+        let spec = LaunchSpec {
+            restart: RestartPolicy::OnFailure,
+            max_restarts: Some(2),
+            ..LaunchSpec::new(&env::temp_dir(), command)
+        };
+        pm.launch("crashy", &spec).expect("Failed to start process");
+
+        // Backoff is 1s then 2s; give it time to burn through both restarts
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            pm.reap_dead();
+            if pm.exit_info("crashy").is_some_and(|e| !e.restarting) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        let exit = pm.exit_info("crashy").expect("Process should be marked crashed");
+        assert_eq!(exit.code, Some(3));
+        assert_eq!(exit.restarts, 2);
+        assert!(!exit.restarting);
+
+        // Output is kept across restarts: one line per run, plus the manager's notes
+        std::thread::sleep(Duration::from_millis(100));
+        let output = pm.get_output("crashy");
+        assert_eq!(output.iter().filter(|l| l.trim() == "tick").count(), 3);
+        assert!(output.iter().any(|l| l.contains("giving up after 2 restarts")));
+
+        // Stopping clears the crash
+        pm.stop("crashy").unwrap();
+        assert!(pm.exit_info("crashy").is_none());
+    }
+
+    #[test]
+    fn test_run_history_kept_after_exit() {
+        let mut pm = ProcessManager::new();
+        let cwd = env::temp_dir();
+
+        #[cfg(windows)]
+        let command = "echo last words & exit 2";
+        #[cfg(not(windows))]
+        let command = "sh -c 'echo last words; exit 2'";
+
+        pm.start("doomed", &cwd, command).expect("Failed to start process");
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pm.is_running("doomed") && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            pm.reap_dead();
+        }
+
+        let run = pm.last_run("doomed").expect("Run should be recorded");
+        assert_eq!(run.code, Some(2));
+        assert!(!run.stopped);
+        assert!(run.ended_at >= run.started_at);
+        assert!(run.tail.iter().any(|l| l.contains("last words")));
+
+        // A stopped process is recorded too, and its output survives the stop
+        #[cfg(windows)]
+        let command = "ping -n 60 127.0.0.1";
+        #[cfg(not(windows))]
+        let command = "sh -c 'echo waiting; exec sleep 60'";
+
+        pm.start("doomed", &cwd, command).expect("Failed to start process");
+        std::thread::sleep(Duration::from_millis(300));
+        pm.stop("doomed").unwrap();
+
+        let history = pm.history("doomed");
+        assert_eq!(history.len(), 2);
+        assert!(history[1].stopped);
+        #[cfg(unix)]
+        assert_eq!(history[1].signal, Some(libc::SIGTERM));
+        #[cfg(unix)]
+        assert!(history[1].tail.iter().any(|l| l.contains("waiting")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pty_launch() {
+        let mut pm = ProcessManager::new();
+        pm.resize_ptys(132, 40);
+        let spec = LaunchSpec {
+            pty: true,
+            ..LaunchSpec::new(&env::temp_dir(), "sh -c 'test -t 1 && echo tty; stty size >&2'")
+        };
+        pm.launch("term", &spec).expect("Failed to start process");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pm.is_running("term") && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            pm.reap_dead();
+        }
+
+        // Both streams arrive through the terminal, unmarked
+        let run = pm.last_run("term").expect("Run should be recorded");
+        assert_eq!(run.tail, vec!["tty", "40 132"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_send_input() {
+        let mut pm = ProcessManager::new();
+        pm.start("echo", &env::temp_dir(), "sh -c 'read line; echo got $line'")
+            .expect("Failed to start process");
+        pm.send_input("echo", "hello\n").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pm.is_running("echo") && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+            pm.reap_dead();
+        }
+        let run = pm.last_run("echo").expect("Run should be recorded");
+        assert_eq!(run.tail, vec!["got hello"]);
+
+        assert!(pm.send_input("echo", "again\n").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stop_kills_process_group_after_grace() {
+        let mut pm = ProcessManager::new();
+        // Ignores SIGTERM, and so does the grandchild it leaves behind
+        let spec = LaunchSpec {
+            stop_timeout: Some(1),
+            ..LaunchSpec::new(&env::temp_dir(), "sh -c 'trap \"\" TERM; sleep 60 & echo $!; wait'")
+        };
+        pm.launch("stubborn", &spec).expect("Failed to start process");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let grandchild = loop {
+            if let Some(pid) = pm.get_output("stubborn").first().and_then(|l| l.parse::<u32>().ok()) {
+                break pid;
+            }
+            assert!(Instant::now() < deadline, "grandchild pid never printed");
+            std::thread::sleep(Duration::from_millis(20));
+        };
+
+        let started = Instant::now();
+        pm.stop("stubborn").unwrap();
+        assert!(started.elapsed() >= Duration::from_secs(1), "SIGTERM was ignored until the grace period ran out");
+        assert!(!proc_tree::is_alive(grandchild));
+        assert_eq!(pm.last_run("stubborn").unwrap().signal, Some(libc::SIGKILL));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stop_many_waits_in_parallel() {
+        let mut pm = ProcessManager::new();
+        let spec = LaunchSpec {
+            stop_timeout: Some(1),
+            ..LaunchSpec::new(&env::temp_dir(), "sh -c 'trap \"\" TERM; while true; do sleep 1; done'")
+        };
+        let names: Vec<String> = (0..3).map(|i| format!("stubborn-{}", i)).collect();
+        for name in &names {
+            pm.launch(name, &spec).expect("Failed to start process");
+        }
+        std::thread::sleep(Duration::from_millis(200));
+
+        let started = Instant::now();
+        pm.stop_many(&names).unwrap();
+        // One grace period for all three, not three in a row
+        assert!(started.elapsed() < Duration::from_millis(2500), "took {:?}", started.elapsed());
+        for name in &names {
+            assert!(!pm.is_running(name));
+            assert_eq!(pm.last_run(name).unwrap().signal, Some(libc::SIGKILL));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stop_in_background_returns_right_away() {
+        let mut pm = ProcessManager::new();
+        let spec = LaunchSpec {
+            stop_timeout: Some(1),
+            ..LaunchSpec::new(&env::temp_dir(), "sh -c 'trap \"\" TERM; while true; do sleep 1; done'")
+        };
+        let names = vec!["stubborn".to_string()];
+        pm.launch(&names[0], &spec).expect("Failed to start process");
+        std::thread::sleep(Duration::from_millis(200));
+
+        let started = Instant::now();
+        let pending = pm.stop_in_background(&names);
+        assert!(started.elapsed() < Duration::from_millis(500), "took {:?}", started.elapsed());
+        assert!(!pm.is_running(&names[0]));
+        assert!(pm.poll_stop(&pending).is_none(), "still in its grace period");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = loop {
+            if let Some(result) = pm.poll_stop(&pending) {
+                break result;
+            }
+            assert!(Instant::now() < deadline, "the stop never finished");
+            std::thread::sleep(Duration::from_millis(50));
+        };
+        result.unwrap();
+        assert_eq!(pm.last_run(&names[0]).unwrap().signal, Some(libc::SIGKILL));
+    }
 }
@@ -0,0 +1,112 @@
+use anyhow::{bail, Result};
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+
+/// The controller side of a pseudo-terminal a process runs in. Output is read
+/// from it, and resizing it sends the process `SIGWINCH`.
+pub struct Pty {
+    master: File,
+}
+
+impl Pty {
+    /// Open a pseudo-terminal of `(cols, rows)` and wire `cmd`'s stdin, stdout
+    /// and stderr to it, making it the controlling terminal of the command.
+    pub fn attach(cmd: &mut Command, size: (u16, u16)) -> Result<Self> {
+        let (mut master, mut slave) = (-1, -1);
+        let winsize = winsize(size);
+        let rc = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                &winsize,
+            )
+        };
+        if rc != 0 {
+            bail!("Cannot open a pseudo-terminal: {}", io::Error::last_os_error());
+        }
+        let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+        // Keep both ends out of other children; the stdio copies are unaffected
+        set_cloexec(master.as_raw_fd())?;
+        set_cloexec(slave.as_raw_fd())?;
+
+        cmd.stdin(slave.try_clone()?)
+            .stdout(slave.try_clone()?)
+            .stderr(slave);
+        unsafe {
+            cmd.pre_exec(|| {
+                // A new session, with the terminal on stdin as its controlling
+                // terminal, so job control and Ctrl-C handling work as usual
+                if libc::setsid() < 0 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        // Programs look at TERM before writing colors or cursor movement
+        if std::env::var_os("TERM").is_none() {
+            cmd.env("TERM", "xterm-256color");
+        }
+
+        Ok(Self { master })
+    }
+
+    /// A handle to the terminal: reading it gives the process's output, and
+    /// writing to it is typing on the process's keyboard.
+    pub fn handle(&self) -> Result<File> {
+        Ok(self.master.try_clone()?)
+    }
+
+    pub fn resize(&self, size: (u16, u16)) -> Result<()> {
+        let winsize = winsize(size);
+        if unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ as _, &winsize) } < 0 {
+            bail!("Cannot resize pseudo-terminal: {}", io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+fn winsize((cols, rows): (u16, u16)) -> libc::winsize {
+    libc::winsize {
+        ws_row: rows.max(1),
+        ws_col: cols.max(1),
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+fn set_cloexec(fd: RawFd) -> Result<()> {
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } < 0 {
+        bail!(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn test_child_sees_a_terminal_of_our_size() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "test -t 1 && echo tty; stty size"]);
+        let pty = Pty::attach(&mut cmd, (100, 30)).unwrap();
+        let mut child = cmd.spawn().unwrap();
+        // Drop our copies of the terminal so reading ends when the child exits
+        drop(cmd);
+
+        let lines: Vec<String> = BufReader::new(pty.handle().unwrap())
+            .lines()
+            .map_while(Result::ok)
+            .collect();
+        child.wait().unwrap();
+        assert_eq!(lines, vec!["tty", "30 100"]);
+
+        pty.resize((120, 40)).unwrap();
+    }
+}
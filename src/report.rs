@@ -0,0 +1,77 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::detect::{self, DetectedProject};
+use crate::git_status::{self, GitStatus};
+use crate::launch;
+use crate::store::ProjectEntry;
+
+/// Machine-readable snapshot of everything the details pane shows for a project.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectReport {
+    #[serde(flatten)]
+    pub entry: ProjectEntry,
+    /// Whether `path` is set and exists on disk
+    pub cloned: bool,
+    /// Effective dev server command: the override if set, otherwise the detected one
+    pub command: Option<String>,
+    pub detection: Option<DetectedProject>,
+    pub git: Option<GitStatus>,
+    pub running: bool,
+    pub port: Option<u16>,
+}
+
+impl ProjectReport {
+    /// Gather detection and git status for a project. Runtime state comes from
+    /// the caller, since only it knows which process manager owns the project.
+    pub fn collect(entry: &ProjectEntry, running: bool, port: Option<u16>) -> Self {
+        let path = Path::new(&entry.path);
+        let cloned = !entry.path.is_empty() && path.exists();
+
+        let (detection, git) = if cloned {
+            (detect::detect(path).ok(), git_status::get_status(path).ok())
+        } else {
+            (None, None)
+        };
+
+        let command = launch::dev_server_command(entry, detection.as_ref());
+
+        Self {
+            entry: entry.clone(),
+            cloned,
+            command,
+            detection,
+            git,
+            running,
+            port,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_json_shape() {
+        let entry = ProjectEntry {
+            name: "ghost".to_string(),
+            repo_url: Some("https://github.com/user/ghost".to_string()),
+            path: String::new(),
+            run_command: Some("npm run dev".to_string()),
+            ..Default::default()
+        };
+
+        let report = ProjectReport::collect(&entry, true, Some(3000));
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+
+        // Entry fields are flattened into the top level
+        assert_eq!(json["name"], "ghost");
+        assert_eq!(json["repo_url"], "https://github.com/user/ghost");
+        assert_eq!(json["cloned"], false);
+        assert_eq!(json["command"], "npm run dev");
+        assert_eq!(json["running"], true);
+        assert_eq!(json["port"], 3000);
+        assert!(json["git"].is_null());
+    }
+}
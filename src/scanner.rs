@@ -101,7 +101,7 @@ fn scan_directory_recursive(dir: &Path, depth: usize, results: &mut Vec<ScannedP
     }
 }
 
-fn get_git_remote(path: &Path) -> Option<String> {
+pub fn get_git_remote(path: &Path) -> Option<String> {
     let output = Command::new("git")
         .current_dir(path)
         .args(["remote", "get-url", "origin"])
@@ -0,0 +1,211 @@
+use anyhow::{bail, Result};
+
+/// How to execute a run command: a program with its arguments, plus any
+/// `NAME=value` assignments that prefixed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+}
+
+/// Characters that need a real shell when they appear unquoted: pipes,
+/// `&&`/`;` chains, redirection, subshells, expansion and globbing.
+const SHELL_CHARS: &[char] = &['|', '&', ';', '<', '>', '(', ')', '`', '$', '*', '?', '~', '#'];
+
+/// Split a command with POSIX shell-words quoting, stripping leading
+/// `NAME=value` assignments into the environment.
+///
+/// Commands that use shell syntax (`a && b`, `a | b`, `> log`, `$VAR`...)
+/// can't be split into argv faithfully, so they run through `/bin/sh -c`.
+pub fn parse(command: &str) -> Result<Invocation> {
+    let words = match split(command)? {
+        Some(words) => words,
+        None => return Ok(sh("/bin/sh", "-c", command)),
+    };
+
+    let mut env = Vec::new();
+    let mut words = words.into_iter().peekable();
+    while let Some(Word { assignment: Some(eq), .. }) = words.peek() {
+        let eq = *eq;
+        let word = words.next().unwrap();
+        env.push((word.text[..eq].to_string(), word.text[eq + 1..].to_string()));
+    }
+
+    let program = match words.next() {
+        Some(word) => word.text,
+        None if env.is_empty() => bail!("Empty command"),
+        None => bail!("Command has only variable assignments"),
+    };
+
+    Ok(Invocation {
+        program,
+        args: words.map(|w| w.text).collect(),
+        env,
+    })
+}
+
+/// Run the command through the user's login shell (`$SHELL -lc`), so it sees
+/// the same PATH and aliases as their terminal.
+pub fn login_shell(command: &str) -> Invocation {
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+    sh(&shell, "-lc", command)
+}
+
+fn sh(shell: &str, flag: &str, command: &str) -> Invocation {
+    Invocation {
+        program: shell.to_string(),
+        args: vec![flag.to_string(), command.to_string()],
+        env: Vec::new(),
+    }
+}
+
+struct Word {
+    text: String,
+    /// Byte offset of the `=` when the word is a `NAME=value` assignment
+    assignment: Option<usize>,
+}
+
+/// Tokenize into words. Returns None when the command contains unquoted shell
+/// syntax and has to be handed to a shell instead.
+fn split(command: &str) -> Result<Option<Vec<Word>>> {
+    let mut words = Vec::new();
+    let mut current: Option<Word> = None;
+    // Whether everything so far in the current word is an unquoted identifier
+    let mut name_so_far = true;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            words.extend(current.take());
+            name_so_far = true;
+            continue;
+        }
+
+        let word = current.get_or_insert_with(|| Word {
+            text: String::new(),
+            assignment: None,
+        });
+
+        match c {
+            '\'' => {
+                name_so_far = false;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.text.push(c),
+                        None => bail!("Unterminated single quote in: {}", command),
+                    }
+                }
+            }
+            '"' => {
+                name_so_far = false;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.text.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.text.push('\\');
+                                word.text.push(c);
+                            }
+                            None => bail!("Unterminated double quote in: {}", command),
+                        },
+                        // Expansion inside double quotes still needs a shell
+                        Some('$' | '`') => return Ok(None),
+                        Some(c) => word.text.push(c),
+                        None => bail!("Unterminated double quote in: {}", command),
+                    }
+                }
+            }
+            '\\' => {
+                name_so_far = false;
+                match chars.next() {
+                    Some('\n') => {}
+                    Some(c) => word.text.push(c),
+                    None => bail!("Trailing backslash in: {}", command),
+                }
+            }
+            '=' if name_so_far && word.assignment.is_none() && !word.text.is_empty() => {
+                word.assignment = Some(word.text.len());
+                word.text.push(c);
+                name_so_far = false;
+            }
+            c if SHELL_CHARS.contains(&c) => return Ok(None),
+            c => {
+                let starts_name = word.text.is_empty() && (c.is_ascii_alphabetic() || c == '_');
+                let continues_name = !word.text.is_empty() && (c.is_ascii_alphanumeric() || c == '_');
+                name_so_far = name_so_far && (starts_name || continues_name);
+                word.text.push(c);
+            }
+        }
+    }
+    words.extend(current);
+
+    Ok(Some(words))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(inv: &Invocation) -> Vec<&str> {
+        std::iter::once(inv.program.as_str())
+            .chain(inv.args.iter().map(|a| a.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_quoting_and_env_prefix() {
+        let inv = parse(r#"FOO=1 BAR="a b" npm run dev -- --title 'My App' --x=y"#).unwrap();
+        assert_eq!(
+            inv.env,
+            vec![
+                ("FOO".to_string(), "1".to_string()),
+                ("BAR".to_string(), "a b".to_string())
+            ]
+        );
+        assert_eq!(
+            argv(&inv),
+            vec!["npm", "run", "dev", "--", "--title", "My App", "--x=y"]
+        );
+
+        // Quoted names and assignments after the program are plain arguments
+        let inv = parse(r#"env "A=1" B=2"#).unwrap();
+        assert!(inv.env.is_empty());
+        assert_eq!(argv(&inv), vec!["env", "A=1", "B=2"]);
+
+        assert_eq!(argv(&parse(r"echo a\ b 'it''s'").unwrap()), vec!["echo", "a b", "its"]);
+    }
+
+    #[test]
+    fn test_shell_syntax_falls_back_to_sh() {
+        for command in [
+            "npm install && npm run dev",
+            "cargo watch | tee log",
+            "python app.py > out.log",
+            "echo $HOME",
+            "echo \"port $PORT\"",
+        ] {
+            let inv = parse(command).unwrap();
+            assert_eq!(argv(&inv), vec!["/bin/sh", "-c", command], "{}", command);
+        }
+
+        // Operators inside quotes are literal
+        assert_eq!(
+            argv(&parse("grep 'a|b' file").unwrap()),
+            vec!["grep", "a|b", "file"]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("FOO=1").is_err());
+        assert!(parse("echo 'unterminated").is_err());
+    }
+}
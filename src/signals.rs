@@ -0,0 +1,31 @@
+#[cfg(unix)]
+static STOP_REQUESTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn handle_stop_signal(_: libc::c_int) {
+    STOP_REQUESTED.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Catch Ctrl-C and SIGTERM, so a foreground `run` or the supervisor can stop
+/// its dev servers cleanly instead of leaving them orphaned.
+pub fn install_stop_handler() {
+    #[cfg(unix)]
+    unsafe {
+        let handler = handle_stop_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+    }
+}
+
+/// True once SIGINT or SIGTERM has arrived.
+pub fn stop_requested() -> bool {
+    #[cfg(unix)]
+    {
+        STOP_REQUESTED.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
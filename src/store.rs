@@ -1,9 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::env;
+use crate::logs::LogSettings;
+use crate::ports::PortSettings;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProjectEntry {
     pub name: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -11,12 +16,287 @@ pub struct ProjectEntry {
     pub path: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub run_command: Option<String>,
+    /// Port the dev server gets whenever it is free, so it stays the same
+    /// between runs. Recorded on the first run that assigns one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Never start the dev server on another port while `port` is taken
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pin_port: bool,
+    /// Run commands through the login shell (`$SHELL -lc`) instead of parsing
+    /// them directly, so they behave exactly as in a terminal
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shell: bool,
+    /// Set `FORCE_COLOR` and `CLICOLOR_FORCE` so tools print colors even
+    /// though their output is captured
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub force_color: bool,
+    /// Run processes under a pseudo-terminal instead of pipes (macOS / Linux)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pty: bool,
+    /// Dotenv files to load, relative to the project path, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_files: Vec<String>,
+    /// Environment variables for every process of the project; values may use
+    /// `${VAR}` and take precedence over `env_files`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// What to do when the dev server or a service exits on its own
+    #[serde(default, skip_serializing_if = "RestartPolicy::is_never")]
+    pub restart: RestartPolicy,
+    /// Restarts allowed in a row before giving up (default 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_restarts: Option<u32>,
+    /// Seconds a stopped process gets to exit after SIGTERM before it and
+    /// everything it started are killed (default 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_timeout: Option<u64>,
+    /// Commands `r` runs before starting anything; `@fetch` and `@install`
+    /// stand for the built-in steps. Defaults to `["@fetch", "@install"]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_run: Option<Vec<Hook>>,
+    /// Commands run once the dev server and services have started
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_start: Vec<Hook>,
+    /// Commands run after the project is stopped from the dashboard
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_stop: Vec<Hook>,
+    /// How to tell that the dev server is up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthCheck>,
+    /// Extra named processes (`[[projects.services]]`) run alongside the project.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<ServiceEntry>,
+}
+
+/// When to restart a dev server or service after it exits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    /// Only after a non-zero exit or a signal
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn is_never(&self) -> bool {
+        *self == RestartPolicy::Never
+    }
+}
+
+/// How to tell that a dev server or service is up: set one of `http`, `tcp`
+/// or `log`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    /// Path to GET on the process's port; a 2xx or 3xx response passes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<String>,
+    /// Pass when the process's port accepts connections
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tcp: bool,
+    /// Regex; pass once a line of output matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>,
+    /// Seconds between checks (default 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u64>,
+    /// Seconds a check may take (default 2)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Seconds the process has to pass its first check before failing checks
+    /// make it unhealthy (default 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_period: Option<u64>,
+}
+
+/// A command run around a project's processes, such as `docker compose up -d`
+/// or a migration. Written as a plain string, or as a table to set options.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "HookDef", into = "HookDef")]
+pub struct Hook {
+    pub command: String,
+    /// Shown in the run progress instead of the command
+    pub name: Option<String>,
+    /// Seconds before the hook is killed (default 600)
+    pub timeout: Option<u64>,
+    /// Defaults to `abort`, except for `@fetch`
+    pub on_failure: Option<OnFailure>,
+}
+
+/// What a failed hook does to the rest of the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnFailure {
+    /// Stop there: nothing after the hook runs
+    Abort,
+    /// Report the failure and carry on
+    Continue,
+}
+
+/// How hooks are written in `projects.toml`.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum HookDef {
+    Command(String),
+    Table {
+        command: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        on_failure: Option<OnFailure>,
+    },
+}
+
+impl From<HookDef> for Hook {
+    fn from(def: HookDef) -> Self {
+        match def {
+            HookDef::Command(command) => Hook::new(&command),
+            HookDef::Table { command, name, timeout, on_failure } => Hook {
+                command,
+                name,
+                timeout,
+                on_failure,
+            },
+        }
+    }
+}
+
+impl From<Hook> for HookDef {
+    fn from(hook: Hook) -> Self {
+        match hook {
+            Hook { command, name: None, timeout: None, on_failure: None } => HookDef::Command(command),
+            Hook { command, name, timeout, on_failure } => HookDef::Table {
+                command,
+                name,
+                timeout,
+                on_failure,
+            },
+        }
+    }
+}
+
+impl Hook {
+    pub const FETCH: &'static str = "@fetch";
+    pub const INSTALL: &'static str = "@install";
+
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            name: None,
+            timeout: None,
+            on_failure: None,
+        }
+    }
+}
+
+/// A named process belonging to a project, such as `web`, `api` or `worker`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ServiceEntry {
+    pub name: String,
+    pub command: String,
+    /// Working directory, relative to the project path unless absolute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<HealthCheck>,
+}
+
+impl ProjectEntry {
+    /// Process key for one of this project's services, e.g. `shop/api`.
+    /// The project's own dev server is keyed by the bare project name.
+    pub fn service_key(&self, service: &str) -> String {
+        format!("{}/{}", self.name, service)
+    }
+
+    /// Find a service by name.
+    pub fn service(&self, name: &str) -> Option<&ServiceEntry> {
+        self.services
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The project's resolved environment: `env_files`, then the `env` table.
+    pub fn resolve_env(&self) -> Vec<(String, String)> {
+        env::resolve(Path::new(&self.path), &self.env_files, &self.env)
+    }
+
+    /// The environment its processes start with: the resolved environment,
+    /// plus the color variables when `force_color` is set.
+    pub fn process_env(&self) -> Vec<(String, String)> {
+        let mut vars = self.resolve_env();
+        if self.force_color {
+            env::force_color(&mut vars);
+        }
+        vars
+    }
+
+    /// The hooks `r` runs first: the configured ones, else the built-in
+    /// fetch and install.
+    pub fn pre_run_hooks(&self) -> Vec<Hook> {
+        match self.pre_run {
+            Some(ref hooks) => hooks.clone(),
+            None => vec![Hook::new(Hook::FETCH), Hook::new(Hook::INSTALL)],
+        }
+    }
+
+    /// Ports this project keeps for itself: its dev server's, then its services'.
+    pub fn reserved_ports(&self) -> Vec<u16> {
+        self.port
+            .into_iter()
+            .chain(self.services.iter().filter_map(|s| s.port))
+            .collect()
+    }
+
+    /// A name for a new service: `name`, else `fallback` when a service is
+    /// already called that, numbered until no service has it.
+    pub fn free_service_name(&self, name: &str, fallback: &str) -> String {
+        let base = match self.service(name) {
+            Some(_) => fallback,
+            None => return name.to_string(),
+        };
+        let mut candidate = base.to_string();
+        let mut n = 2;
+        while self.service(&candidate).is_some() {
+            candidate = format!("{}-{}", base, n);
+            n += 1;
+        }
+        candidate
+    }
+
+    /// Every process key this project can own: its dev server, then each service.
+    pub fn process_keys(&self) -> Vec<String> {
+        std::iter::once(self.name.clone())
+            .chain(self.services.iter().map(|s| self.service_key(&s.name)))
+            .collect()
+    }
+}
+
+impl ServiceEntry {
+    /// Resolve the working directory against the project's path.
+    pub fn resolve_cwd(&self, project_path: &Path) -> PathBuf {
+        match self.cwd {
+            Some(ref cwd) => project_path.join(cwd),
+            None => project_path.to_path_buf(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProjectStore {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub install_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "LogSettings::is_default")]
+    pub logs: LogSettings,
+    #[serde(default, skip_serializing_if = "PortSettings::is_default")]
+    pub ports: PortSettings,
     #[serde(default)]
     pub projects: Vec<ProjectEntry>,
 
@@ -42,12 +322,17 @@ impl ProjectStore {
         let mut store = if path.exists() {
             let content = fs::read_to_string(&path)?;
             let mut store: ProjectStore = toml::from_str(&content)?;
+            store
+                .ports
+                .validate()
+                .with_context(|| format!("Invalid [ports] settings in {}", path.display()))?;
             store.first_run = false;
             store
         } else {
-            let mut store = ProjectStore::default();
-            store.first_run = true;
-            store
+            ProjectStore {
+                first_run: true,
+                ..Default::default()
+            }
         };
         store.file_path = path;
         store.sort_projects();
@@ -102,13 +387,48 @@ impl ProjectStore {
             .find(|p| p.name.eq_ignore_ascii_case(name))
     }
 
+    /// Keep the port a project's dev server was first given, so it gets the
+    /// same one next time. Returns whether anything changed.
+    pub fn remember_port(&mut self, name: &str, port: u16) -> bool {
+        match self.get_mut(name) {
+            Some(project) if project.port.is_none() => {
+                project.port = Some(port);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Ports reserved by projects other than `name`, which its dev server
+    /// shouldn't be given.
+    pub fn ports_reserved_by_others(&self, name: &str) -> Vec<u16> {
+        self.projects
+            .iter()
+            .filter(|p| !p.name.eq_ignore_ascii_case(name))
+            .flat_map(|p| p.reserved_ports())
+            .collect()
+    }
+
+    /// Other projects that reserve the same port as the dev server of `name`.
+    pub fn port_conflicts(&self, name: &str) -> Vec<&str> {
+        let port = match self.get(name).and_then(|p| p.port) {
+            Some(port) => port,
+            None => return Vec::new(),
+        };
+        self.projects
+            .iter()
+            .filter(|p| !p.name.eq_ignore_ascii_case(name) && p.reserved_ports().contains(&port))
+            .map(|p| p.name.as_str())
+            .collect()
+    }
+
     /// Get the install directory as an expanded, absolute path.
     /// Returns None if the stored path is not absolute (invalid).
     pub fn get_install_dir(&self) -> Option<PathBuf> {
         self.install_dir.as_ref().and_then(|dir| {
-            let path = if dir.starts_with("~/") {
+            let path = if let Some(rest) = dir.strip_prefix("~/") {
                 if let Some(home) = dirs::home_dir() {
-                    home.join(&dir[2..])
+                    home.join(rest)
                 } else {
                     return None;
                 }
@@ -126,8 +446,7 @@ impl ProjectStore {
 
     /// Sort projects by name, case-insensitive.
     fn sort_projects(&mut self) {
-        self.projects
-            .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        self.projects.sort_by_key(|p| p.name.to_lowercase());
     }
 }
 
@@ -145,6 +464,7 @@ mod tests {
             repo_url: Some("https://github.com/user/my-project".to_string()),
             path: "/home/user/my-project".to_string(),
             run_command: None,
+            ..Default::default()
         });
         assert_eq!(store.projects.len(), 1);
 
@@ -154,6 +474,7 @@ mod tests {
             repo_url: None,
             path: "/other/path".to_string(),
             run_command: None,
+            ..Default::default()
         });
         assert_eq!(store.projects.len(), 1);
 
@@ -164,13 +485,16 @@ mod tests {
 
     #[test]
     fn test_serialization_roundtrip() {
-        let mut store = ProjectStore::default();
-        store.install_dir = Some("~/Projects".to_string());
+        let mut store = ProjectStore {
+            install_dir: Some("~/Projects".to_string()),
+            ..Default::default()
+        };
         store.add(ProjectEntry {
             name: "alpha".to_string(),
             repo_url: Some("https://github.com/user/alpha".to_string()),
             path: "/home/user/alpha".to_string(),
             run_command: Some("npm start".to_string()),
+            ..Default::default()
         });
 
         // Serialize to TOML string
@@ -192,4 +516,136 @@ mod tests {
             Some("npm start".to_string())
         );
     }
+
+    #[test]
+    fn test_log_settings_roundtrip() {
+        let store = ProjectStore {
+            logs: LogSettings {
+                max_age_days: 30,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let toml_str = toml::to_string_pretty(&store).expect("serialize");
+        let restored: ProjectStore = toml::from_str(&toml_str).expect("deserialize");
+
+        assert_eq!(restored.logs.max_age_days, 30);
+        assert_eq!(restored.logs.max_file_mb, 10);
+    }
+
+    #[test]
+    fn test_services_from_toml() {
+        let toml_str = r#"
+            [[projects]]
+            name = "shop"
+            path = "/home/user/shop"
+
+            [[projects.services]]
+            name = "web"
+            command = "pnpm dev"
+            cwd = "apps/web"
+            port = 3000
+
+            [[projects.services]]
+            name = "worker"
+            command = "node worker.js"
+            env = { QUEUE = "default" }
+        "#;
+
+        let store: ProjectStore = toml::from_str(toml_str).expect("deserialize");
+        let project = &store.projects[0];
+        assert_eq!(project.services.len(), 2);
+
+        let web = project.service("web").unwrap();
+        assert_eq!(web.port, Some(3000));
+        assert_eq!(
+            web.resolve_cwd(Path::new("/home/user/shop")),
+            PathBuf::from("/home/user/shop/apps/web")
+        );
+
+        let worker = project.service("worker").unwrap();
+        assert_eq!(worker.env.get("QUEUE").map(|s| s.as_str()), Some("default"));
+        assert_eq!(
+            project.process_keys(),
+            vec!["shop", "shop/web", "shop/worker"]
+        );
+    }
+
+    #[test]
+    fn test_hooks_from_toml() {
+        let toml_str = r#"
+            [[projects]]
+            name = "shop"
+            path = "/home/user/shop"
+            pre_run = ["@install", { command = "docker compose up -d", timeout = 60, on_failure = "continue" }]
+            post_stop = ["docker compose down"]
+        "#;
+
+        let store: ProjectStore = toml::from_str(toml_str).expect("deserialize");
+        let project = &store.projects[0];
+        let pre_run = project.pre_run_hooks();
+        assert_eq!(pre_run[0], Hook::new(Hook::INSTALL));
+        assert_eq!(pre_run[1].timeout, Some(60));
+        assert_eq!(pre_run[1].on_failure, Some(OnFailure::Continue));
+        assert_eq!(project.post_stop, vec![Hook::new("docker compose down")]);
+
+        // Plain hooks stay plain strings when saved
+        let saved = toml::to_string_pretty(&store).expect("serialize");
+        assert!(saved.contains(r#"post_stop = ["docker compose down"]"#), "{}", saved);
+        let restored: ProjectStore = toml::from_str(&saved).expect("deserialize");
+        assert_eq!(restored.projects[0].pre_run, project.pre_run);
+
+        // Without `pre_run` the built-in steps run
+        let bare = ProjectEntry::default();
+        assert_eq!(
+            bare.pre_run_hooks(),
+            vec![Hook::new(Hook::FETCH), Hook::new(Hook::INSTALL)]
+        );
+    }
+
+    #[test]
+    fn test_port_reservations() {
+        let toml_str = r#"
+            [[projects]]
+            name = "blog"
+            path = "/home/user/blog"
+            port = 3000
+            pin_port = true
+
+            [[projects]]
+            name = "shop"
+            path = "/home/user/shop"
+            port = 3000
+
+            [[projects.services]]
+            name = "api"
+            command = "cargo run"
+            port = 8080
+        "#;
+
+        let store: ProjectStore = toml::from_str(toml_str).expect("deserialize");
+        assert!(store.get("blog").unwrap().pin_port);
+        assert_eq!(store.ports_reserved_by_others("blog"), vec![3000, 8080]);
+        assert_eq!(store.ports_reserved_by_others("shop"), vec![3000]);
+        assert_eq!(store.port_conflicts("blog"), vec!["shop"]);
+        assert!(store.port_conflicts("ghost").is_empty());
+    }
+
+    #[test]
+    fn test_free_service_name() {
+        let mut project = ProjectEntry {
+            name: "mono".to_string(),
+            ..Default::default()
+        };
+        for name in ["web", "apps-web"] {
+            project.services.push(ServiceEntry {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+        assert_eq!(project.free_service_name("api", "apps-api"), "api");
+        assert_eq!(project.free_service_name("web", "sites-web"), "sites-web");
+        assert_eq!(project.free_service_name("web", "apps-web"), "apps-web-2");
+    }
 }
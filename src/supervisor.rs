@@ -0,0 +1,500 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::health::Health;
+use crate::process::{describe_exit, format_duration, unix_now, LaunchSpec, DEFAULT_STOP_TIMEOUT};
+
+/// How long to wait for the reply to a request the supervisor answers right
+/// away, such as a snapshot
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Added to the grace period when waiting for a stop: after it, survivors are
+/// killed and the ports given time to be released
+const STOP_SLACK: Duration = Duration::from_secs(5);
+
+/// A request sent to the supervisor, one JSON object per line.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    Ping,
+    Start {
+        name: String,
+        spec: LaunchSpec,
+    },
+    Stop {
+        name: String,
+    },
+    /// Stop several processes side by side
+    StopMany {
+        names: Vec<String>,
+    },
+    Snapshot,
+    Output {
+        name: String,
+        since: usize,
+    },
+    History {
+        name: String,
+    },
+    /// Text for a process's stdin
+    Input {
+        name: String,
+        data: String,
+    },
+    /// Terminal size for processes run under a pseudo-terminal
+    Resize {
+        cols: u16,
+        rows: u16,
+    },
+    Shutdown,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Ok,
+    Error { message: String },
+    Snapshot {
+        processes: Vec<ProcessSnapshot>,
+        #[serde(default)]
+        exited: Vec<ExitInfo>,
+    },
+    Output { lines: Vec<String>, total: usize },
+    History { runs: Vec<RunRecord> },
+}
+
+/// Runtime state of one supervised process, as reported to clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    pub name: String,
+    pub pid: u32,
+    pub port: Option<u16>,
+    /// The process was seen listening on `port`
+    #[serde(default)]
+    pub port_confirmed: bool,
+    /// Times the process was restarted since it last ran stably
+    #[serde(default)]
+    pub restarts: u32,
+    /// Set for processes with a health check
+    #[serde(default)]
+    pub health: Option<Health>,
+    /// Seconds the process is given to exit before it is killed
+    #[serde(default)]
+    pub stop_timeout: Option<u64>,
+}
+
+/// How a managed process last exited. Only reported for processes that are
+/// not running: ones that crashed, or are waiting to be restarted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitInfo {
+    pub name: String,
+    /// Exit code; None when the process was killed by a signal
+    pub code: Option<i32>,
+    #[serde(default)]
+    pub signal: Option<i32>,
+    pub restarts: u32,
+    /// A restart is scheduled
+    pub restarting: bool,
+}
+
+/// One finished run of a managed process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// Unix timestamps, in seconds
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    /// Stopped on request, rather than exiting on its own
+    pub stopped: bool,
+    /// Last lines of output
+    pub tail: Vec<String>,
+    /// Full output of the run, when written to disk
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+}
+
+impl RunRecord {
+    /// One-line summary, e.g. "exited (exit code 1) after 3m 12s, 5s ago".
+    pub fn summary(&self) -> String {
+        let how = if self.stopped {
+            "stopped".to_string()
+        } else {
+            format!("exited ({})", describe_exit(self.code, self.signal))
+        };
+        format!(
+            "{} after {}, {} ago",
+            how,
+            format_duration(self.ended_at.saturating_sub(self.started_at)),
+            format_duration(unix_now().saturating_sub(self.ended_at))
+        )
+    }
+}
+
+/// Returns the path to `~/.claude-manager/supervisor.sock`.
+pub fn socket_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude-manager").join("supervisor.sock"))
+}
+
+/// Connection to a running supervisor. Keeps the last process snapshot so the
+/// TUI can answer "is it running?" every frame without a round trip per call.
+#[derive(Clone)]
+pub struct SupervisorClient {
+    snapshot: Vec<ProcessSnapshot>,
+    exited: Vec<ExitInfo>,
+}
+
+impl SupervisorClient {
+    /// Connect to the supervisor if one is listening. Returns None when no
+    /// supervisor is running (or on platforms without Unix sockets).
+    pub fn connect() -> Option<Self> {
+        let mut client = Self {
+            snapshot: Vec::new(),
+            exited: Vec::new(),
+        };
+        client.refresh().ok()?;
+        Some(client)
+    }
+
+    /// Send one request and wait up to `timeout` for its response.
+    pub fn request(&self, request: &Request, timeout: Duration) -> Result<Response> {
+        #[cfg(unix)]
+        {
+            use std::io::{BufRead, BufReader, Write};
+            use std::os::unix::net::UnixStream;
+
+            let path = match socket_path() {
+                Some(p) => p,
+                None => bail!("Cannot find home directory"),
+            };
+            let mut stream = UnixStream::connect(path)?;
+            stream.set_read_timeout(Some(timeout))?;
+
+            let mut line = serde_json::to_string(request)?;
+            line.push('\n');
+            stream.write_all(line.as_bytes())?;
+
+            let mut reply = String::new();
+            BufReader::new(&stream).read_line(&mut reply)?;
+            match serde_json::from_str(&reply)? {
+                Response::Error { message } => bail!(message),
+                response => Ok(response),
+            }
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = (request, timeout);
+            bail!("The supervisor is only available on Unix")
+        }
+    }
+
+    /// Send a request that only acknowledges success.
+    pub fn send(&self, request: &Request, timeout: Duration) -> Result<()> {
+        self.request(request, timeout).map(|_| ())
+    }
+
+    /// How long stopping `names` can take: the longest of their grace
+    /// periods, plus the time to kill survivors and wait for their ports.
+    pub fn stop_timeout(&self, names: &[String]) -> Duration {
+        let grace = names
+            .iter()
+            .map(|name| {
+                self.snapshot
+                    .iter()
+                    .find(|p| &p.name == name)
+                    .and_then(|p| p.stop_timeout)
+                    .unwrap_or(DEFAULT_STOP_TIMEOUT)
+            })
+            .max()
+            .unwrap_or(0);
+        Duration::from_secs(grace) + STOP_SLACK
+    }
+
+    /// Re-fetch the process snapshot.
+    pub fn refresh(&mut self) -> Result<()> {
+        if let Response::Snapshot { processes, exited } = self.request(&Request::Snapshot, REPLY_TIMEOUT)? {
+            self.snapshot = processes;
+            self.exited = exited;
+        }
+        Ok(())
+    }
+
+    pub fn processes(&self) -> &[ProcessSnapshot] {
+        &self.snapshot
+    }
+
+    pub fn exited(&self) -> &[ExitInfo] {
+        &self.exited
+    }
+
+    pub fn history(&self, name: &str) -> Vec<RunRecord> {
+        let request = Request::History {
+            name: name.to_string(),
+        };
+        match self.request(&request, REPLY_TIMEOUT) {
+            Ok(Response::History { runs }) => runs,
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn output_since(&self, name: &str, since: usize) -> (Vec<String>, usize) {
+        let request = Request::Output {
+            name: name.to_string(),
+            since,
+        };
+        match self.request(&request, REPLY_TIMEOUT) {
+            Ok(Response::Output { lines, total }) => (lines, total),
+            _ => (Vec::new(), since),
+        }
+    }
+}
+
+/// Run the supervisor in the foreground until it receives `Shutdown`, SIGINT
+/// or SIGTERM. Every dev server it starts is stopped on the way out.
+#[cfg(unix)]
+pub fn serve() -> Result<()> {
+    use std::fs;
+    use std::io::ErrorKind;
+    use std::os::unix::net::UnixListener;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use crate::process::ProcessManager;
+    use crate::signals;
+
+    let path = match socket_path() {
+        Some(p) => p,
+        None => bail!("Cannot find home directory"),
+    };
+    if SupervisorClient::connect().is_some() {
+        bail!("A supervisor is already running ({})", path.display());
+    }
+    // Left behind by a supervisor that didn't shut down cleanly
+    let _ = fs::remove_file(&path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(&path)?;
+    listener.set_nonblocking(true)?;
+    signals::install_stop_handler();
+
+    let pm = Arc::new(Mutex::new(ProcessManager::new()));
+    let shutdown = Arc::new(AtomicBool::new(false));
+
+    while !shutdown.load(Ordering::SeqCst) && !signals::stop_requested() {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if let Ok(mut pm) = pm.lock() {
+                    pm.reap_dead();
+                }
+                std::thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        // Each connection gets its own thread, so a slow request such as a
+        // stop waiting out its grace period doesn't hold up other clients
+        let pm = Arc::clone(&pm);
+        let shutdown = Arc::clone(&shutdown);
+        std::thread::spawn(move || {
+            let _ = serve_connection(stream, &pm, &shutdown);
+        });
+    }
+
+    if let Ok(mut pm) = pm.lock() {
+        let _ = pm.stop_all();
+    }
+    let _ = fs::remove_file(&path);
+    Ok(())
+}
+
+/// Read one request from a client and write back the response.
+#[cfg(unix)]
+fn serve_connection(
+    stream: std::os::unix::net::UnixStream,
+    pm: &std::sync::Mutex<crate::process::ProcessManager>,
+    shutdown: &std::sync::atomic::AtomicBool,
+) -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::sync::atomic::Ordering;
+
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(Request::Shutdown) => {
+            shutdown.store(true, Ordering::SeqCst);
+            Response::Ok
+        }
+        Ok(Request::Stop { name }) => stop(pm, &[name])?,
+        Ok(Request::StopMany { names }) => stop(pm, &names)?,
+        Ok(request) => handle(&mut *lock(pm)?, request),
+        Err(e) => Response::Error {
+            message: format!("Bad request: {}", e),
+        },
+    };
+
+    let mut reply = serde_json::to_string(&response)?;
+    reply.push('\n');
+    (&stream).write_all(reply.as_bytes())?;
+    Ok(())
+}
+
+/// Only signalling and bookkeeping hold the lock; the wait in between doesn't,
+/// so other requests are answered while the processes exit.
+#[cfg(unix)]
+fn stop(pm: &std::sync::Mutex<crate::process::ProcessManager>, names: &[String]) -> Result<Response> {
+    let mut stopping = lock(pm)?.begin_stop(names);
+    stopping.wait();
+    let result = lock(pm)?.finish_stop(stopping);
+    Ok(respond(result.map(|()| Response::Ok)))
+}
+
+#[cfg(unix)]
+fn lock(
+    pm: &std::sync::Mutex<crate::process::ProcessManager>,
+) -> Result<std::sync::MutexGuard<'_, crate::process::ProcessManager>> {
+    pm.lock().map_err(|_| anyhow::anyhow!("Supervisor state is poisoned"))
+}
+
+#[cfg(unix)]
+fn respond(result: Result<Response>) -> Response {
+    result.unwrap_or_else(|e| Response::Error {
+        message: e.to_string(),
+    })
+}
+
+#[cfg(unix)]
+fn handle(pm: &mut crate::process::ProcessManager, request: Request) -> Response {
+    let result = match request {
+        Request::Ping | Request::Shutdown => Ok(Response::Ok),
+        Request::Start { name, spec } => {
+            // Pick up log settings changed since the supervisor started
+            if let Ok(store) = crate::store::ProjectStore::load() {
+                pm.set_logging(&store.logs);
+            }
+            pm.reap_dead();
+            if pm.is_running(&name) {
+                Err(anyhow::anyhow!("'{}' is already running", name))
+            } else {
+                pm.launch(&name, &spec).map(|()| Response::Ok)
+            }
+        }
+        Request::Stop { name } => pm.stop(&name).map(|()| Response::Ok),
+        Request::StopMany { names } => pm.stop_many(&names).map(|()| Response::Ok),
+        Request::Input { name, data } => pm.send_input(&name, &data).map(|()| Response::Ok),
+        Request::Resize { cols, rows } => {
+            pm.resize_ptys(cols, rows);
+            Ok(Response::Ok)
+        }
+        Request::Snapshot => {
+            pm.reap_dead();
+            Ok(Response::Snapshot {
+                processes: pm.snapshot(),
+                exited: pm.exited(),
+            })
+        }
+        Request::Output { name, since } => {
+            let (lines, total) = pm.get_output_since(&name, since);
+            Ok(Response::Output { lines, total })
+        }
+        Request::History { name } => Ok(Response::History {
+            runs: pm.history(&name),
+        }),
+    };
+
+    respond(result)
+}
+
+/// Launch `claude-manager daemon` detached from this terminal and wait for its
+/// socket to come up.
+#[cfg(unix)]
+pub fn spawn_detached() -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    if SupervisorClient::connect().is_some() {
+        bail!("A supervisor is already running");
+    }
+
+    let mut cmd = Command::new(std::env::current_exe()?);
+    cmd.arg("daemon")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // New session: closing the terminal must not take the supervisor with it
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+    cmd.spawn()?;
+
+    for _ in 0..40 {
+        if SupervisorClient::connect().is_some() {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    bail!("Supervisor did not start")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_roundtrip() {
+        let request = Request::Start {
+            name: "shop/web".to_string(),
+            spec: LaunchSpec {
+                port: Some(3000),
+                ..LaunchSpec::new(&PathBuf::from("/tmp/web"), "npm run dev")
+            },
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(json.contains(r#""type":"start""#));
+
+        match serde_json::from_str::<Request>(&json).unwrap() {
+            Request::Start { name, spec } => {
+                assert_eq!(name, "shop/web");
+                assert_eq!(spec.port, Some(3000));
+            }
+            other => panic!("Unexpected request: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_stop_timeout_follows_grace_period() {
+        let snapshot = |name: &str, stop_timeout| ProcessSnapshot {
+            name: name.to_string(),
+            pid: 1,
+            port: None,
+            port_confirmed: false,
+            restarts: 0,
+            health: None,
+            stop_timeout,
+        };
+        let client = SupervisorClient {
+            snapshot: vec![snapshot("api", Some(30)), snapshot("web", None)],
+            exited: Vec::new(),
+        };
+
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(client.stop_timeout(&names(&["api", "web"])), Duration::from_secs(30) + STOP_SLACK);
+        assert_eq!(
+            client.stop_timeout(&names(&["web"])),
+            Duration::from_secs(DEFAULT_STOP_TIMEOUT) + STOP_SLACK
+        );
+    }
+}
@@ -32,22 +32,17 @@ impl Tui {
 
             // Check if app wants to quit
             if app.should_quit() {
-                // Stop all running processes before quitting
-                for name in app.process_manager.running_projects() {
-                    let _ = app.process_manager.stop(&name);
-                }
+                app.shutdown();
                 break;
             }
 
             if event::poll(Duration::from_millis(100))? {
                 match event::read()? {
-                    Event::Key(key) => {
-                        if key.kind == KeyEventKind::Press {
-                            if key.code == KeyCode::Char('q') && !app.is_input_mode() {
-                                app.request_quit();
-                            } else {
-                                app.handle_key(key.code);
-                            }
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        if key.code == KeyCode::Char('q') && !app.is_input_mode() {
+                            app.request_quit();
+                        } else {
+                            app.handle_key(key.code);
                         }
                     }
                     Event::Paste(text) => {
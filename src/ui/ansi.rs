@@ -0,0 +1,245 @@
+use std::ops::Range;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+/// Split a line of terminal output into styled spans, following its ANSI SGR
+/// sequences (`ESC [ ... m`). Other escape sequences, such as cursor movement
+/// or OSC hyperlinks, are dropped. A carriage return in the middle of a line
+/// overwrites what came before it, as a progress bar would in a terminal.
+pub fn parse(line: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    let mut text = String::new();
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    // Parameters and intermediates, up to the final byte
+                    let mut params = String::new();
+                    let mut last = None;
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            last = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+                    if last == Some('m') {
+                        flush(&mut spans, &mut text, style);
+                        style = apply_sgr(style, &params);
+                    }
+                }
+                Some(']') => {
+                    // OSC, terminated by BEL or ST (`ESC \`)
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // Two-character escapes (charset selection and the like)
+                Some(_) | None => {}
+            },
+            '\r' => {
+                spans.clear();
+                text.clear();
+            }
+            c if c.is_control() && c != '\t' => {}
+            c => text.push(c),
+        }
+    }
+    flush(&mut spans, &mut text, style);
+    spans
+}
+
+/// A line of terminal output as a styled `Line`.
+pub fn to_line(line: &str) -> Line<'static> {
+    Line::from(parse(line))
+}
+
+/// The text of a line without its escape sequences.
+pub fn strip(line: &str) -> String {
+    parse(line).iter().map(|s| s.content.as_ref()).collect()
+}
+
+/// Patch `style` over the byte ranges of the spans' combined text, splitting
+/// spans where a range starts or ends inside them. Ranges must be sorted.
+pub fn highlight(spans: Vec<Span<'static>>, ranges: &[Range<usize>], style: Style) -> Vec<Span<'static>> {
+    if ranges.is_empty() {
+        return spans;
+    }
+
+    let mut out = Vec::new();
+    let mut pos = 0;
+    for span in spans {
+        let text = span.content.as_ref();
+        let end = pos + text.len();
+        let mut cut = pos;
+
+        // Boundaries of the ranges that fall inside this span
+        let mut bounds: Vec<usize> = ranges
+            .iter()
+            .flat_map(|r| [r.start, r.end])
+            .filter(|&b| b > pos && b < end)
+            .collect();
+        bounds.push(end);
+
+        for b in bounds {
+            if b <= cut {
+                continue;
+            }
+            let piece = text[cut - pos..b - pos].to_string();
+            let inside = ranges.iter().any(|r| r.start <= cut && cut < r.end);
+            let piece_style = if inside { span.style.patch(style) } else { span.style };
+            out.push(Span::styled(piece, piece_style));
+            cut = b;
+        }
+        pos = end;
+    }
+    out
+}
+
+fn flush(spans: &mut Vec<Span<'static>>, text: &mut String, style: Style) {
+    if !text.is_empty() {
+        spans.push(Span::styled(std::mem::take(text), style));
+    }
+}
+
+/// Apply the parameters of one SGR sequence, e.g. `1;31` or `38;5;208`.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    // `ESC[m` is a reset; ignore anything that isn't a plain number list
+    let codes: Vec<u16> = params
+        .split([';', ':'])
+        .map(|p| if p.is_empty() { Some(0) } else { p.parse().ok() })
+        .collect::<Option<_>>()
+        .unwrap_or_default();
+
+    let mut codes = codes.into_iter();
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 | 6 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            8 => style.add_modifier(Modifier::HIDDEN),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            28 => style.remove_modifier(Modifier::HIDDEN),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(basic_color(code - 30)),
+            38 => match extended_color(&mut codes) {
+                Some(color) => style.fg(color),
+                None => style,
+            },
+            39 => Style { fg: None, ..style },
+            40..=47 => style.bg(basic_color(code - 40)),
+            48 => match extended_color(&mut codes) {
+                Some(color) => style.bg(color),
+                None => style,
+            },
+            49 => Style { bg: None, ..style },
+            90..=97 => style.fg(bright_color(code - 90)),
+            100..=107 => style.bg(bright_color(code - 100)),
+            _ => style,
+        };
+    }
+    style
+}
+
+/// `5;n` (256-color palette) or `2;r;g;b` (true color) after a 38 or 48.
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(Color::Indexed(codes.next()? as u8)),
+        2 => {
+            let (r, g, b) = (codes.next()?, codes.next()?, codes.next()?);
+            Some(Color::Rgb(r as u8, g as u8, b as u8))
+        }
+        _ => None,
+    }
+}
+
+fn basic_color(n: u16) -> Color {
+    match n {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::Gray,
+    }
+}
+
+fn bright_color(n: u16) -> Color {
+    match n {
+        0 => Color::DarkGray,
+        1 => Color::LightRed,
+        2 => Color::LightGreen,
+        3 => Color::LightYellow,
+        4 => Color::LightBlue,
+        5 => Color::LightMagenta,
+        6 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sgr() {
+        let spans = parse("\x1b[1;31merror\x1b[0m: \x1b[38;5;208mwarn\x1b[39;48;2;1;2;3m bg\x1b[m done");
+        let styled: Vec<(&str, Style)> = spans.iter().map(|s| (s.content.as_ref(), s.style)).collect();
+        assert_eq!(
+            styled,
+            vec![
+                ("error", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                (": ", Style::default()),
+                ("warn", Style::default().fg(Color::Indexed(208))),
+                (" bg", Style::default().bg(Color::Rgb(1, 2, 3))),
+                (" done", Style::default()),
+            ]
+        );
+
+        // Non-SGR sequences, OSC hyperlinks and overwritten progress are dropped
+        assert_eq!(
+            strip("\x1b[2K\x1b]8;;http://x\x1b\\link\x1b]8;;\x07 10%\r\x1b[32mdone\x1b[0m"),
+            "done"
+        );
+        assert_eq!(strip("plain\r"), "plain");
+    }
+
+    #[test]
+    fn test_highlight_splits_spans() {
+        let spans = parse("ab\x1b[31mcdef\x1b[0mgh");
+        let mark = Style::default().bg(Color::Yellow);
+        let out = highlight(spans, &[1..3, 5..7], mark);
+        let styled: Vec<(&str, Style)> = out.iter().map(|s| (s.content.as_ref(), s.style)).collect();
+        assert_eq!(
+            styled,
+            vec![
+                ("a", Style::default()),
+                ("b", mark),
+                ("c", Style::default().fg(Color::Red).bg(Color::Yellow)),
+                ("de", Style::default().fg(Color::Red)),
+                ("f", Style::default().fg(Color::Red).bg(Color::Yellow)),
+                ("g", mark),
+                ("h", Style::default()),
+            ]
+        );
+    }
+}
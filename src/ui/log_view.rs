@@ -0,0 +1,428 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use regex::{Regex, RegexBuilder};
+
+use crate::theme;
+use crate::ui::ansi;
+
+/// Prefix the process manager puts on lines read from stderr.
+const STDERR_PREFIX: &str = "[stderr] ";
+
+/// Which output stream to show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFilter {
+    All,
+    Stdout,
+    Stderr,
+}
+
+impl StreamFilter {
+    fn next(self) -> Self {
+        match self {
+            StreamFilter::All => StreamFilter::Stdout,
+            StreamFilter::Stdout => StreamFilter::Stderr,
+            StreamFilter::Stderr => StreamFilter::All,
+        }
+    }
+
+    fn accepts(self, line: &str) -> bool {
+        match self {
+            StreamFilter::All => true,
+            StreamFilter::Stdout => !line.starts_with(STDERR_PREFIX),
+            StreamFilter::Stderr => line.starts_with(STDERR_PREFIX),
+        }
+    }
+}
+
+/// Full-screen log viewer with scrolling, follow mode, regex search and a
+/// stdout/stderr filter.
+pub struct LogView {
+    pub visible: bool,
+    title: String,
+    lines: Vec<String>,
+    /// First visible line, as an index into the filtered lines
+    offset: usize,
+    /// Stick to the bottom as new lines arrive
+    follow: bool,
+    filter: StreamFilter,
+    search: Option<Regex>,
+    /// Pattern being typed after `/`
+    search_input: Option<String>,
+    search_error: Option<String>,
+    /// Filtered line index of the match last jumped to
+    current_match: Option<usize>,
+    /// Lines that fit on screen, from the last render
+    page: usize,
+}
+
+impl LogView {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            title: String::new(),
+            lines: Vec::new(),
+            offset: 0,
+            follow: true,
+            filter: StreamFilter::All,
+            search: None,
+            search_input: None,
+            search_error: None,
+            current_match: None,
+            page: 20,
+        }
+    }
+
+    pub fn show(&mut self, title: &str) {
+        *self = Self {
+            visible: true,
+            title: title.to_string(),
+            page: self.page,
+            ..Self::new()
+        };
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.lines.clear();
+    }
+
+    /// Replace the content, e.g. with the latest output of a running process.
+    pub fn set_lines(&mut self, lines: Vec<String>) {
+        self.lines = lines;
+        self.clamp();
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        if let Some(ref mut input) = self.search_input {
+            input.push_str(text);
+        }
+    }
+
+    fn filtered(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .map(|l| l.as_str())
+            .filter(|l| self.filter.accepts(l))
+            .collect()
+    }
+
+    fn max_offset(&self) -> usize {
+        self.filtered().len().saturating_sub(self.page)
+    }
+
+    fn clamp(&mut self) {
+        let max = self.max_offset();
+        if self.follow || self.offset > max {
+            self.offset = max;
+        }
+    }
+
+    fn scroll_up(&mut self, n: usize) {
+        self.follow = false;
+        self.offset = self.offset.saturating_sub(n);
+    }
+
+    fn scroll_down(&mut self, n: usize) {
+        self.offset = (self.offset + n).min(self.max_offset());
+        // Scrolling back to the bottom resumes following
+        if self.offset == self.max_offset() {
+            self.follow = true;
+        }
+    }
+
+    /// Jump to the next (or previous) line matching the search, wrapping around.
+    fn jump_to_match(&mut self, forward: bool) {
+        let re = match self.search {
+            Some(ref re) => re,
+            None => return,
+        };
+        let matches: Vec<usize> = self
+            .filtered()
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| re.is_match(&ansi::strip(l)))
+            .map(|(i, _)| i)
+            .collect();
+        if matches.is_empty() {
+            self.current_match = None;
+            return;
+        }
+
+        // Search on from the current match, or from the screen like `less` does
+        let next = match (forward, self.current_match) {
+            (true, Some(c)) => matches.iter().find(|&&i| i > c),
+            (true, None) => matches.iter().find(|&&i| i >= self.offset),
+            (false, Some(c)) => matches.iter().rev().find(|&&i| i < c),
+            (false, None) => matches.iter().rev().find(|&&i| i < self.offset + self.page),
+        };
+        let next = next.or(if forward { matches.first() } else { matches.last() });
+
+        if let Some(&idx) = next {
+            self.current_match = Some(idx);
+            self.follow = false;
+            self.offset = idx.saturating_sub(self.page / 2).min(self.max_offset());
+        }
+    }
+
+    fn apply_search(&mut self, pattern: &str) {
+        self.search_error = None;
+        self.current_match = None;
+        if pattern.is_empty() {
+            self.search = None;
+            return;
+        }
+        // Smart case: case-insensitive unless the pattern has capitals
+        let insensitive = !pattern.chars().any(|c| c.is_uppercase());
+        match RegexBuilder::new(pattern).case_insensitive(insensitive).build() {
+            Ok(re) => {
+                self.search = Some(re);
+                self.jump_to_match(true);
+            }
+            Err(e) => {
+                self.search = None;
+                self.search_error = Some(e.to_string().lines().last().unwrap_or("").to_string());
+            }
+        }
+    }
+
+    /// Handle a key. Returns false when the viewer was closed.
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        if let Some(ref mut input) = self.search_input {
+            match key {
+                KeyCode::Enter => {
+                    let pattern = input.clone();
+                    self.search_input = None;
+                    self.apply_search(&pattern);
+                }
+                KeyCode::Esc => self.search_input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return true;
+        }
+
+        match key {
+            KeyCode::Esc if self.search.is_some() => {
+                self.search = None;
+                self.current_match = None;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                return false;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_up(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_down(1),
+            KeyCode::PageUp => self.scroll_up(self.page.max(1)),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_down(self.page.max(1)),
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.follow = false;
+                self.offset = 0;
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.follow = true;
+                self.clamp();
+            }
+            KeyCode::Char('f') => {
+                self.follow = !self.follow;
+                self.clamp();
+            }
+            KeyCode::Char('s') => {
+                self.filter = self.filter.next();
+                self.current_match = None;
+                self.clamp();
+            }
+            KeyCode::Char('/') => self.search_input = Some(String::new()),
+            KeyCode::Char('n') => self.jump_to_match(true),
+            KeyCode::Char('N') => self.jump_to_match(false),
+            _ => {}
+        }
+        true
+    }
+
+    /// Spans for one line: output colors kept, stderr marked, search matches
+    /// highlighted.
+    fn styled_line(&self, line: &str, current: bool) -> Line<'static> {
+        let mut spans = Vec::new();
+        let text = match line.strip_prefix(STDERR_PREFIX) {
+            Some(rest) => {
+                spans.push(Span::styled(STDERR_PREFIX, Style::default().fg(theme::DANGER)));
+                rest
+            }
+            None => line,
+        };
+
+        let colored = ansi::parse(text);
+        match self.search {
+            Some(ref re) => {
+                let plain: String = colored.iter().map(|s| s.content.as_ref()).collect();
+                let ranges: Vec<_> = re
+                    .find_iter(&plain)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| m.range())
+                    .collect();
+                let match_style = Style::default().fg(Color::Black).bg(theme::WARNING);
+                spans.extend(ansi::highlight(colored, &ranges, match_style));
+            }
+            None => spans.extend(colored),
+        }
+
+        let line = Line::from(spans);
+        if current {
+            line.style(theme::highlight())
+        } else {
+            line
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, area: Rect) {
+        if !self.visible {
+            return;
+        }
+        frame.render_widget(Clear, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(area);
+
+        self.page = (chunks[0].height as usize).saturating_sub(2).max(1);
+        self.clamp();
+
+        let filtered = self.filtered();
+        let end = (self.offset + self.page).min(filtered.len());
+        let visible: Vec<Line> = filtered[self.offset.min(end)..end]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| self.styled_line(line, self.current_match == Some(self.offset + i)))
+            .collect();
+
+        let mut title = vec![
+            Span::styled(format!(" Logs: {} ", self.title), theme::accent_title()),
+            if self.follow {
+                Span::styled("\u{2502} following ", theme::status_running())
+            } else {
+                Span::styled("\u{2502} paused ", Style::default().fg(theme::WARNING))
+            },
+        ];
+        match self.filter {
+            StreamFilter::All => {}
+            StreamFilter::Stdout => title.push(Span::styled("\u{2502} stdout only ", theme::label())),
+            StreamFilter::Stderr => title.push(Span::styled("\u{2502} stderr only ", theme::label())),
+        }
+        if let Some(ref re) = self.search {
+            let count = filtered.iter().filter(|l| re.is_match(&ansi::strip(l))).count();
+            title.push(Span::styled(
+                format!("\u{2502} /{} ({} lines) ", re.as_str(), count),
+                Style::default().fg(theme::WARNING),
+            ));
+        }
+        title.push(Span::styled(
+            format!("\u{2502} {}-{} of {} ", self.offset + 1, end, filtered.len()),
+            theme::label(),
+        ));
+
+        let para = Paragraph::new(visible).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Line::from(title))
+                .border_style(theme::active_border()),
+        );
+        frame.render_widget(para, chunks[0]);
+
+        let footer = if let Some(ref input) = self.search_input {
+            Line::from(vec![
+                Span::styled(" /", theme::accent_title()),
+                Span::raw(input.as_str()),
+                Span::styled("\u{2588}", theme::accent_title()),
+            ])
+        } else if let Some(ref err) = self.search_error {
+            Line::from(Span::styled(
+                format!(" Invalid pattern: {}", err),
+                Style::default().fg(theme::DANGER),
+            ))
+        } else {
+            Line::from(Span::styled(
+                " [j/k] scroll  [PgUp/PgDn] page  [g/G] top/bottom  [f]ollow  [/] search  [n/N] next/prev  [s]tream  [q] close",
+                theme::label(),
+            ))
+        };
+        frame.render_widget(Paragraph::new(footer), chunks[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(lines: &[&str]) -> LogView {
+        let mut view = LogView::new();
+        view.show("test");
+        view.page = 3;
+        view.set_lines(lines.iter().map(|l| l.to_string()).collect());
+        view
+    }
+
+    #[test]
+    fn test_follow_and_scroll() {
+        let mut view = view(&["1", "2", "3", "4", "5", "6"]);
+        assert_eq!(view.offset, 3);
+
+        // Scrolling up pauses; new lines no longer move the view
+        view.handle_key(KeyCode::Char('k'));
+        assert!(!view.follow);
+        view.set_lines((1..=10).map(|i| i.to_string()).collect());
+        assert_eq!(view.offset, 2);
+
+        // Back at the bottom, following resumes
+        view.handle_key(KeyCode::Char('G'));
+        assert!(view.follow);
+        assert_eq!(view.offset, 7);
+    }
+
+    #[test]
+    fn test_search_and_stream_filter() {
+        let mut view = view(&[
+            "compiling",
+            "[stderr] error: first",
+            "ok",
+            "ok",
+            "[stderr] warning",
+            "ok",
+            "[stderr] ERROR: second",
+        ]);
+
+        for c in "/error".chars() {
+            view.handle_key(KeyCode::Char(c));
+        }
+        view.handle_key(KeyCode::Enter);
+        // Smart case matches "ERROR" too; the first match on screen comes first
+        assert_eq!(view.current_match, Some(6));
+        view.handle_key(KeyCode::Char('n'));
+        assert_eq!(view.current_match, Some(1), "search wraps around");
+        view.handle_key(KeyCode::Char('N'));
+        assert_eq!(view.current_match, Some(6));
+
+        // stdout, then stderr only
+        view.handle_key(KeyCode::Char('s'));
+        assert_eq!(view.filtered().len(), 4);
+        view.handle_key(KeyCode::Char('s'));
+        assert_eq!(view.filtered(), vec!["[stderr] error: first", "[stderr] warning", "[stderr] ERROR: second"]);
+
+        // A bad pattern is reported, not applied
+        for c in "/(".chars() {
+            view.handle_key(KeyCode::Char(c));
+        }
+        view.handle_key(KeyCode::Enter);
+        assert!(view.search.is_none());
+        assert!(view.search_error.is_some());
+    }
+}
@@ -1,2 +1,4 @@
+pub mod ansi;
 pub mod input;
+pub mod log_view;
 pub mod selector;
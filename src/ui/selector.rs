@@ -9,19 +9,48 @@ use ratatui::{
 use crate::theme;
 
 pub struct RepoSelector {
+    title: String,
     pub visible: bool,
     pub repos: Vec<(String, String)>, // (name, url)
     pub state: ListState,
     pub filter: String,
+    /// Whether Space marks several items to pick at once
+    multi: bool,
+    /// Data of the marked items, in the order they were marked
+    marked: Vec<String>,
 }
 
 impl RepoSelector {
     pub fn new() -> Self {
+        Self::with_title("Select Repository")
+    }
+
+    pub fn with_title(title: &str) -> Self {
         Self {
+            title: title.to_string(),
             visible: false,
             repos: Vec::new(),
             state: ListState::default(),
             filter: String::new(),
+            multi: false,
+            marked: Vec::new(),
+        }
+    }
+
+    /// A selector where Space marks items and Enter picks all of them.
+    pub fn multi_select(title: &str) -> Self {
+        Self {
+            multi: true,
+            ..Self::with_title(title)
+        }
+    }
+
+    /// The items picked with Enter: the marked ones, or else the one Enter
+    /// was pressed on.
+    pub fn take_marked(&mut self, picked: String) -> Vec<String> {
+        match self.marked.is_empty() {
+            true => vec![picked],
+            false => std::mem::take(&mut self.marked),
         }
     }
 
@@ -29,6 +58,7 @@ impl RepoSelector {
         self.repos = repos;
         self.visible = true;
         self.filter.clear();
+        self.marked.clear();
         self.state.select(Some(0));
     }
 
@@ -85,6 +115,17 @@ impl RepoSelector {
                 }
                 None
             }
+            KeyCode::Char(' ') if self.multi => {
+                if let Some((_, data)) = self.state.selected().and_then(|i| filtered.get(i)) {
+                    match self.marked.iter().position(|m| m == data) {
+                        Some(pos) => {
+                            self.marked.remove(pos);
+                        }
+                        None => self.marked.push(data.clone()),
+                    }
+                }
+                None
+            }
             KeyCode::Backspace => {
                 self.filter.pop();
                 // Reset selection when filter changes
@@ -126,13 +167,20 @@ impl RepoSelector {
 
         let items: Vec<ListItem> = filtered
             .iter()
-            .map(|(name, _url)| {
-                ListItem::new(Line::from(vec![Span::raw(name.clone())]))
+            .map(|(name, data)| {
+                let mut spans = vec![Span::raw(name.clone())];
+                if self.multi {
+                    let mark = if self.marked.contains(data) { "[x] " } else { "[ ] " };
+                    spans.insert(0, Span::styled(mark, theme::label()));
+                }
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
-        let title = if self.filter.is_empty() {
-            " Select Repository (type to filter) ".to_string()
+        let title = if self.filter.is_empty() && self.multi {
+            format!(" {} (Space to mark, Enter to add) ", self.title)
+        } else if self.filter.is_empty() {
+            format!(" {} (type to filter) ", self.title)
         } else {
             format!(" Filter: {} ", self.filter)
         };
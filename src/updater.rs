@@ -154,7 +154,7 @@ pub fn apply_update(info: &UpdateInfo) -> anyhow::Result<()> {
                 "--max-time",
                 "120",
                 "-o",
-                &archive_path.to_string_lossy().to_string(),
+                archive_path.to_string_lossy().as_ref(),
                 &info.download_url,
             ])
             .stdin(std::process::Stdio::null())
@@ -169,9 +169,9 @@ pub fn apply_update(info: &UpdateInfo) -> anyhow::Result<()> {
         let status = Command::new("tar")
             .args([
                 "-xf",
-                &archive_path.to_string_lossy().to_string(),
+                archive_path.to_string_lossy().as_ref(),
                 "-C",
-                &temp_dir.to_string_lossy().to_string(),
+                temp_dir.to_string_lossy().as_ref(),
             ])
             .status()?;
         if !status.success() {
@@ -196,7 +196,7 @@ pub fn apply_update(info: &UpdateInfo) -> anyhow::Result<()> {
                 "--max-time",
                 "120",
                 "-o",
-                &archive_path.to_string_lossy().to_string(),
+                archive_path.to_string_lossy().as_ref(),
                 &info.download_url,
             ])
             .status()?;
@@ -209,9 +209,9 @@ pub fn apply_update(info: &UpdateInfo) -> anyhow::Result<()> {
         let status = Command::new("tar")
             .args([
                 "-xzf",
-                &archive_path.to_string_lossy().to_string(),
+                archive_path.to_string_lossy().as_ref(),
                 "-C",
-                &temp_dir.to_string_lossy().to_string(),
+                temp_dir.to_string_lossy().as_ref(),
             ])
             .status()?;
         if !status.success() {
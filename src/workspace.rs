@@ -0,0 +1,329 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::detect::{self, PackageManager, PortStyle};
+
+/// Scripts worth running as a dev server, in order of preference.
+const DEV_SCRIPTS: &[&str] = &["dev", "start", "serve", "watch"];
+
+/// Directories never searched for packages.
+const SKIP_DIRS: &[&str] = &["node_modules", "target", "dist", "build"];
+
+/// How deep `**` in a workspace pattern searches.
+const MAX_DEPTH: usize = 4;
+
+/// A package of a JS, Nx or Cargo workspace that can run on its own.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkspacePackage {
+    pub name: String,
+    /// Directory relative to the workspace root, with `/` separators
+    pub dir: String,
+    /// Scripts (or Nx targets) it defines
+    pub scripts: Vec<String>,
+    /// How to run it from its directory, if it has a dev script or a binary
+    pub run_command: Option<String>,
+    /// How its run command takes a port
+    pub port_style: PortStyle,
+}
+
+impl WorkspacePackage {
+    /// Name for the service it becomes: the package name without its npm
+    /// scope, since `/` separates a project from its services.
+    pub fn service_name(&self) -> String {
+        let name = self.name.rsplit('/').next().unwrap_or(&self.name);
+        name.trim_start_matches('@').to_string()
+    }
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    name: Option<String>,
+    scripts: Option<HashMap<String, String>>,
+    workspaces: Option<Workspaces>,
+}
+
+/// `"workspaces": [...]` or yarn's `"workspaces": { "packages": [...] }`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Workspaces {
+    List(Vec<String>),
+    Table { packages: Vec<String> },
+}
+
+#[derive(Deserialize)]
+struct NxProject {
+    name: Option<String>,
+    targets: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Packages of the JS workspace at `path` (pnpm, npm, yarn, bun, with or
+/// without Turborepo or Nx on top), run with `pm`.
+pub fn js_packages(path: &Path, pm: PackageManager) -> Vec<WorkspacePackage> {
+    let mut patterns = pnpm_patterns(path);
+    if patterns.is_empty() {
+        patterns = read_package_json(path)
+            .and_then(|p| p.workspaces)
+            .map(|w| match w {
+                Workspaces::List(list) | Workspaces::Table { packages: list } => list,
+            })
+            .unwrap_or_default();
+    }
+    // Nx repos without package manager workspaces use its default layout
+    if patterns.is_empty() && path.join("nx.json").exists() {
+        patterns = ["apps/*", "packages/*", "libs/*"].map(String::from).to_vec();
+    }
+
+    expand(path, &patterns)
+        .into_iter()
+        .filter_map(|dir| js_package(path, &dir, pm))
+        .collect()
+}
+
+fn js_package(root: &Path, dir: &str, pm: PackageManager) -> Option<WorkspacePackage> {
+    let path = root.join(dir);
+    let fallback_name = dir.rsplit('/').next().unwrap_or(dir).to_string();
+
+    if let Some(pkg) = read_package_json(&path) {
+        let scripts = pkg.scripts.unwrap_or_default();
+        let script = DEV_SCRIPTS.iter().find(|s| scripts.contains_key(**s));
+        let run_command = script.map(|s| format!("{} run {}", pm.as_str(), s));
+        let script_text = script.and_then(|s| scripts.get(*s)).map_or("", |s| s.as_str());
+        let port_style = detect::js_port_style(script_text);
+        let mut names: Vec<String> = scripts.into_keys().collect();
+        names.sort();
+        return Some(WorkspacePackage {
+            name: pkg.name.unwrap_or(fallback_name),
+            dir: dir.to_string(),
+            scripts: names,
+            run_command,
+            port_style,
+        });
+    }
+
+    // An Nx project without a package.json of its own
+    let content = fs::read_to_string(path.join("project.json")).ok()?;
+    let project: NxProject = serde_json::from_str(&content).ok()?;
+    let name = project.name.unwrap_or(fallback_name);
+    let mut targets: Vec<String> = project.targets.unwrap_or_default().into_keys().collect();
+    targets.sort();
+    let run_command = ["serve", "dev"]
+        .iter()
+        .find(|t| targets.iter().any(|name| name == *t))
+        .map(|t| format!("npx nx run {}:{}", name, t));
+    // Nx's serve executors take `--port`
+    Some(WorkspacePackage {
+        name,
+        dir: dir.to_string(),
+        scripts: targets,
+        run_command,
+        port_style: PortStyle::Arg("--port={}"),
+    })
+}
+
+/// The `packages:` list of `pnpm-workspace.yaml`.
+fn pnpm_patterns(path: &Path) -> Vec<String> {
+    let content = match fs::read_to_string(path.join("pnpm-workspace.yaml")) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    let mut patterns = Vec::new();
+    let mut in_packages = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if !line.starts_with([' ', '\t', '-']) && !trimmed.is_empty() {
+            in_packages = trimmed.starts_with("packages:");
+        } else if in_packages {
+            if let Some(item) = trimmed.strip_prefix('-') {
+                let item = item.split(" #").next().unwrap_or("").trim();
+                patterns.push(item.trim_matches(['\'', '"']).to_string());
+            }
+        }
+    }
+    patterns
+}
+
+fn read_package_json(path: &Path) -> Option<PackageJson> {
+    let content = fs::read_to_string(path.join("package.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Members of the Cargo workspace at `path` that have a binary to run.
+pub fn cargo_packages(path: &Path) -> Vec<WorkspacePackage> {
+    let members = fs::read_to_string(path.join("Cargo.toml"))
+        .ok()
+        .and_then(|c| c.parse::<toml::Table>().ok())
+        .and_then(|t| t.get("workspace")?.get("members")?.as_array().cloned())
+        .unwrap_or_default();
+    let patterns: Vec<String> = members
+        .iter()
+        .filter_map(|m| m.as_str().map(String::from))
+        .collect();
+
+    expand(path, &patterns)
+        .into_iter()
+        .filter_map(|dir| {
+            let manifest = fs::read_to_string(path.join(&dir).join("Cargo.toml")).ok()?;
+            let manifest: toml::Table = manifest.parse().ok()?;
+            let name = manifest.get("package")?.get("name")?.as_str()?.to_string();
+            let has_bin = manifest.contains_key("bin") || path.join(&dir).join("src/main.rs").exists();
+            Some(WorkspacePackage {
+                run_command: has_bin.then(|| format!("cargo run -p {}", name)),
+                name,
+                dir,
+                scripts: Vec::new(),
+                port_style: PortStyle::Env("PORT"),
+            })
+        })
+        .collect()
+}
+
+/// Directories under `root` matching workspace patterns such as `apps/*`,
+/// `packages/**` or `tools/cli`, minus those excluded with `!`. Returned
+/// relative to `root`, sorted.
+fn expand(root: &Path, patterns: &[String]) -> Vec<String> {
+    let clean = |p: &str| p.trim_start_matches("./").trim_end_matches('/').to_string();
+    let excluded: Vec<String> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix('!'))
+        .map(clean)
+        .collect();
+
+    let mut dirs = Vec::new();
+    for pattern in patterns.iter().filter(|p| !p.starts_with('!')) {
+        let segments: Vec<String> = clean(pattern).split('/').map(String::from).collect();
+        walk(root, "", &segments, 0, &mut dirs);
+    }
+    dirs.retain(|d| !excluded.iter().any(|e| glob_match(e, d)));
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+fn walk(root: &Path, rel: &str, segments: &[String], depth: usize, out: &mut Vec<String>) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            if !rel.is_empty() {
+                out.push(rel.to_string());
+            }
+            return;
+        }
+    };
+    let join = |name: &str| match rel.is_empty() {
+        true => name.to_string(),
+        false => format!("{}/{}", rel, name),
+    };
+
+    if !segment.contains('*') {
+        if root.join(join(segment)).is_dir() {
+            walk(root, &join(segment), rest, depth, out);
+        }
+        return;
+    }
+
+    let entries = match fs::read_dir(root.join(rel)) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let mut children: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| e.file_name().to_str().map(String::from))
+        .filter(|name| !name.starts_with('.') && !SKIP_DIRS.contains(&name.as_str()))
+        .collect();
+    children.sort();
+
+    if segment == "**" {
+        // Zero or more directories
+        walk(root, rel, rest, depth, out);
+        if depth < MAX_DEPTH {
+            for child in &children {
+                walk(root, &join(child), segments, depth + 1, out);
+            }
+        }
+    } else {
+        for child in children.iter().filter(|c| wildcard(segment, c)) {
+            walk(root, &join(child), rest, depth, out);
+        }
+    }
+}
+
+/// Whether a relative directory matches a pattern, segment by segment.
+fn glob_match(pattern: &str, dir: &str) -> bool {
+    let (pattern, dir): (Vec<&str>, Vec<&str>) = (pattern.split('/').collect(), dir.split('/').collect());
+    match pattern.last() {
+        Some(&"**") => pattern.len() - 1 <= dir.len()
+            && pattern[..pattern.len() - 1].iter().zip(&dir).all(|(p, d)| wildcard(p, d)),
+        _ => pattern.len() == dir.len() && pattern.iter().zip(&dir).all(|(p, d)| wildcard(p, d)),
+    }
+}
+
+/// Match one path segment against a pattern where `*` is any run of characters.
+fn wildcard(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let tail = match name.strip_prefix(prefix) {
+                Some(tail) => tail,
+                None => return false,
+            };
+            (0..=tail.len())
+                .filter(|&i| tail.is_char_boundary(i))
+                .any(|i| wildcard(rest, &tail[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, file: &str, content: &str) {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_workspace_packages() {
+        let temp = std::env::temp_dir().join("claude-manager-workspace-test");
+        let _ = fs::remove_dir_all(&temp);
+
+        write(&temp, "pnpm-workspace.yaml", "packages:\n  - 'apps/*'\n  - \"packages/**\"\n  - '!packages/internal'\n");
+        write(&temp, "apps/web/package.json", r#"{"name": "@acme/web", "scripts": {"dev": "vite", "build": "vite build"}}"#);
+        write(&temp, "apps/docs/package.json", r#"{"name": "docs", "scripts": {"start": "astro dev"}}"#);
+        write(&temp, "packages/ui/package.json", r#"{"name": "@acme/ui", "scripts": {"build": "tsc"}}"#);
+        write(&temp, "packages/internal/package.json", r#"{"name": "internal"}"#);
+        write(&temp, "packages/ui/node_modules/dep/package.json", r#"{"name": "dep"}"#);
+
+        let packages = js_packages(&temp, PackageManager::Pnpm);
+        let summary: Vec<(&str, &str, Option<&str>)> = packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.dir.as_str(), p.run_command.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("docs", "apps/docs", Some("pnpm run start")),
+                ("@acme/web", "apps/web", Some("pnpm run dev")),
+                ("@acme/ui", "packages/ui", None),
+            ]
+        );
+        assert_eq!(packages[1].scripts, vec!["build", "dev"]);
+        assert_eq!(packages[1].service_name(), "web");
+        assert_eq!(packages[1].port_style, PortStyle::Arg("--port {}"));
+
+        write(&temp, "Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n");
+        write(&temp, "crates/api/Cargo.toml", "[package]\nname = \"api\"\n");
+        write(&temp, "crates/api/src/main.rs", "fn main() {}");
+        write(&temp, "crates/core/Cargo.toml", "[package]\nname = \"core\"\n");
+        let crates = cargo_packages(&temp);
+        assert_eq!(crates.len(), 2);
+        assert_eq!(crates[0].run_command.as_deref(), Some("cargo run -p api"));
+        assert_eq!(crates[1].run_command, None);
+
+        let _ = fs::remove_dir_all(&temp);
+    }
+}